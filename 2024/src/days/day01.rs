@@ -1,15 +1,20 @@
 use core::panic;
-use std::io::{self};
 use std::{collections::HashMap, fmt::Error};
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// Two columns of location IDs.
+pub const INPUT: Shape = Shape::Lines("<uint> <uint>");
+
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
     day1_part1()?;
     day1_part2()?;
     Ok(())
 }
 
 fn day1_part1() -> Result<(), Error> {
-    let data = match read_from_file("day1/part1.txt") {
+    let data = match input::read("day1/part1.txt") {
         Ok(d) => d,
         Err(e) => {
             panic!("Error reading file: {}", e);
@@ -48,12 +53,12 @@ fn day1_part1() -> Result<(), Error> {
         sum += (left[i] - right[i]).abs();
     }
 
-    println!("Day 1 Part 1: {}", sum);
+    answer(1, sum);
     Ok(())
 }
 
 fn day1_part2() -> Result<(), Error> {
-    let data = match read_from_file("day1/part1.txt") {
+    let data = match input::read("day1/part1.txt") {
         Ok(d) => d,
         Err(_) => {
             return Err(Error);
@@ -98,18 +103,6 @@ fn day1_part2() -> Result<(), Error> {
         sum += left_val * right_count;
     }
 
-    println!("Day 1 Part 2: {}", sum);
+    answer(2, sum);
     Ok(())
 }
-
-fn read_from_file(file_path: &str) -> Result<String, io::Error> {
-    // Read the data from the specified file path
-    let data = match std::fs::read_to_string(file_path) {
-        Ok(d) => d,
-        Err(e) => {
-            println!("Error reading file");
-            return Err(e);
-        }
-    };
-    Ok(data)
-}
@@ -1,89 +1,149 @@
-use std::io::{self};
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = read_from_file("day2/part1.txt")?;
+/// One report of levels per line.
+pub const INPUT: Shape = Shape::Lines("<ints>");
 
-    let mut safe_reports = 0;
+#[derive(clap::Args, Debug, Clone)]
+#[group(id = "day02_options")]
+pub struct Options {
+    /// Day 2: smallest allowed difference between adjacent levels
+    #[clap(long, default_value_t = 1)]
+    pub min_step: i64,
 
-    for report in data.lines() {
-        // each report is a space-separated list of ints
-        let split = report.split_whitespace();
-        let mut vals = Vec::new();
-        for val in split {
-            match val.parse::<i32>() {
-                Ok(v) => vals.push(v),
-                Err(_) => continue,
-            }
-        }
-        if is_safe_report(&vals) {
-            safe_reports += 1;
+    /// Day 2: largest allowed difference between adjacent levels
+    #[clap(long, default_value_t = 3)]
+    pub max_step: i64,
+
+    /// Day 2: also count reports that are safe after removing up to this many
+    /// levels
+    #[clap(long)]
+    pub tolerance: Option<usize>,
+
+    /// Day 2: print which levels were removed to make each report safe
+    #[clap(long)]
+    pub explain: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            min_step: 1,
+            max_step: 3,
+            tolerance: None,
+            explain: false,
         }
     }
-    println!("Day 2 Part 1: {}", safe_reports);
-
-    let mut safe_reports = 0;
-    for report in data.lines() {
-        // Same as the above, but a report is safe if we can remove any single level and still have a safe report
-        let split = report.split_whitespace();
-        let mut vals = Vec::new();
-        for val in split {
-            match val.parse::<i32>() {
-                Ok(v) => vals.push(v),
-                Err(_) => continue,
-            }
-        }
+}
+
+/// Red-nosed reports. Using `day2/sample.txt` gives 2 and 4.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day02;
+    let data = input::read("day2/part1.txt")?;
+    let reports = parse_reports(&data)?;
+    let steps = options.min_step..=options.max_step;
+
+    let count_safe = |tolerance| {
+        reports
+            .iter()
+            .filter(|r| levels_to_remove(r, &steps, tolerance).is_some())
+            .count()
+    };
 
-        if is_safe_report(&vals) {
-            safe_reports += 1;
-            continue;
-        } else {
-            // Try removing each value and check if the report is safe
-            for i in 0..vals.len() {
-                let mut new_vals = vals.clone();
-                new_vals.remove(i);
-                if is_safe_report(&new_vals) {
-                    safe_reports += 1;
-                    break;
+    answer(1, count_safe(0));
+    answer(2, count_safe(1));
+
+    if let Some(k) = options.tolerance {
+        println!("Day 2 tolerating {} removals: {}", k, count_safe(k));
+    }
+
+    if options.explain {
+        let tolerance = options.tolerance.unwrap_or(1);
+        for (n, report) in reports.iter().enumerate() {
+            match levels_to_remove(report, &steps, tolerance) {
+                Some(removed) if removed.is_empty() => {}
+                Some(removed) => {
+                    let levels: Vec<String> = removed
+                        .iter()
+                        .map(|&i| format!("level {} ({})", i + 1, report[i]))
+                        .collect();
+                    println!("report {}: remove {}", n + 1, levels.join(", "));
                 }
+                None => println!("report {}: unsafe", n + 1),
             }
         }
     }
-    println!("Day 2 Part 2: {}", safe_reports);
+
     Ok(())
 }
 
-fn is_safe_report(vals: &[i32]) -> bool {
-    // if vals is either stricly increasing or strictly decreasing by 1, 2, or 3 increment safe_reports
-    // get the differences between each element
-    let mut differences = Vec::new();
-    for i in 0..vals.len() - 1 {
-        differences.push(vals[i + 1] - vals[i]);
-    }
+fn parse_reports(data: &str) -> Result<Vec<Vec<i64>>, std::num::ParseIntError> {
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.split_whitespace().map(str::parse).collect())
+        .collect()
+}
 
-    let min = differences.iter().min().unwrap();
-    let max = differences.iter().max().unwrap();
-    if *min < -3 || *max > 3 {
-        return false;
-    }
-    if *min < 0 && *max > 0 {
-        return false;
-    }
+/// The fewest levels (by index) to remove, at most `tolerance` of them, so
+/// the report is strictly increasing or strictly decreasing with every
+/// difference in `steps`. `None` if that takes more than `tolerance`.
+/// Reports with fewer than two levels left are trivially safe.
+fn levels_to_remove(
+    report: &[i64],
+    steps: &std::ops::RangeInclusive<i64>,
+    tolerance: usize,
+) -> Option<Vec<usize>> {
+    [1, -1]
+        .into_iter()
+        .filter_map(|sign| removals_for_direction(report, steps, sign, tolerance))
+        .min_by_key(|removed| removed.len())
+}
 
-    if differences.iter().all(|&x| x >= -3 && x <= 3 && x != 0) {
-        return true;
-    } else {
+/// `best[i]` is the fewest removals so far that keep level `i` as the last
+/// level kept, with `from[i]` the level kept before it. A level can only
+/// follow one of the `tolerance + 1` levels before it, so this is linear in
+/// the report length for a fixed tolerance.
+fn removals_for_direction(
+    report: &[i64],
+    steps: &std::ops::RangeInclusive<i64>,
+    sign: i64,
+    tolerance: usize,
+) -> Option<Vec<usize>> {
+    let n = report.len();
+    if n == 0 {
+        return Some(Vec::new());
     }
-    false
-}
 
-fn read_from_file(file_path: &str) -> Result<String, io::Error> {
-    // Read the data from the specified file path
-    let data = match std::fs::read_to_string(file_path) {
-        Ok(d) => d,
-        Err(e) => {
-            println!("Error reading file");
-            return Err(e);
+    let mut best = vec![usize::MAX; n];
+    let mut from: Vec<Option<usize>> = vec![None; n];
+
+    for i in 0..n {
+        // Keep level `i` as the first level, removing all before it.
+        if i <= tolerance {
+            best[i] = i;
         }
-    };
-    Ok(data)
+        for j in i.saturating_sub(tolerance + 1)..i {
+            let skipped = i - j - 1;
+            if best[j] == usize::MAX || !steps.contains(&((report[i] - report[j]) * sign)) {
+                continue;
+            }
+            let removed = best[j] + skipped;
+            if removed <= tolerance && removed < best[i] {
+                best[i] = removed;
+                from[i] = Some(j);
+            }
+        }
+    }
+
+    let last = (0..n)
+        .filter(|&i| best[i] != usize::MAX && best[i] + (n - 1 - i) <= tolerance)
+        .min_by_key(|&i| best[i] + (n - 1 - i))?;
+
+    let mut kept = vec![false; n];
+    let mut at = Some(last);
+    while let Some(i) = at {
+        kept[i] = true;
+        at = from[i];
+    }
+    Some((0..n).filter(|&i| !kept[i]).collect())
 }
@@ -1,90 +1,231 @@
-use regex::Regex;
-use std::io::{self};
-
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_from_file("day3/part1.txt") {
-        Ok(d) => d,
-        Err(e) => {
-            return Err(Box::new(e));
-        }
-    };
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// Corrupted memory, which can be anything.
+pub const INPUT: Shape = Shape::Text;
+
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day03_options")]
+pub struct Options {
+    /// Day 3: list every instruction with its byte offset and whether its
+    /// `mul` was counted in part 2
+    #[clap(long)]
+    pub trace: bool,
+
+    /// Day 3: scan this file instead of day3/part1.txt. It is streamed, so it
+    /// can be far bigger than memory
+    #[clap(long)]
+    pub memory_dump: Option<PathBuf>,
+}
 
-    // Sample input is xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))
-    // What I want is only the mul(number,number) instructions, which can be done with regex
-    // I want to get all of the valid instances of mul(number,number) and then multiply the
-    // two numbers together and get the sum
-    let re = match Regex::new(r"mul\((\d+),(\d+)\)") {
-        Ok(r) => r,
-        Err(e) => {
-            return Err(Box::new(e));
+/// Mull it over. Using `day3/sample.txt` gives 161 for part 1 and
+/// `day3/sample2.txt` gives 48 for part 2.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day03;
+    let tokens = match &options.memory_dump {
+        Some(path) => interpret(
+            Tokenizer::new(BufReader::new(File::open(path)?)),
+            options.trace,
+        ),
+        None => {
+            let memory = input::read("day3/part1.txt")?;
+            interpret(Tokenizer::new(memory.as_bytes()), options.trace)
         }
     };
+    let (part1, part2) = tokens?;
 
-    let mut sum = 0;
-    for cap in re.captures_iter(&data) {
-        let num1 = match cap[1].parse::<i32>() {
-            Ok(n) => n,
-            Err(_) => continue,
-        };
-        let num2 = match cap[2].parse::<i32>() {
-            Ok(n) => n,
-            Err(_) => continue,
-        };
-        sum += num1 * num2;
-    }
+    answer(1, part1);
+    answer(2, part2);
+
+    Ok(())
+}
 
-    println!("Day 3 Part 1: {:?}", sum);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Mul(u32, u32),
+    Do,
+    Dont,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    /// Byte offset of the instruction's first character.
+    offset: u64,
+    instruction: Instruction,
+}
 
-    // For part two we also want to capture the instructions for 'do()' and "don't()", when
-    // we hit "don't" we stop processing until we hit "do" again
-    let re = match Regex::new(r"mul\((\d+),(\d+)\)|do\(\)|don't\(\)") {
-        Ok(r) => r,
-        Err(e) => {
-            return Err(Box::new(e));
+/// Where the scanner is within a possible instruction. Only the first byte
+/// of any instruction is `m` or `d`, so after a mismatch the scan restarts at
+/// the offending byte without missing anything.
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Idle,
+    /// Matched this many bytes of `mul(`.
+    Mul(usize),
+    /// Matched this many bytes of `don't()`; `do()` branches off after `do`.
+    Do(usize),
+    /// Matched `do(`.
+    DoOpen,
+    /// Digits of the first operand so far.
+    First(u32, usize),
+    /// The first operand and digits of the second so far.
+    Second(u32, u32, usize),
+}
+
+/// Operands are 1 to 3 digits; `mul(1234,5)` is corrupted.
+const MAX_DIGITS: usize = 3;
+
+/// Streams instructions out of a corrupted memory dump.
+struct Tokenizer<R> {
+    reader: R,
+    scanner: Scanner,
+}
+
+impl<R: BufRead> Tokenizer<R> {
+    fn new(reader: R) -> Tokenizer<R> {
+        Tokenizer {
+            reader,
+            scanner: Scanner {
+                state: State::Idle,
+                offset: 0,
+                start: 0,
+            },
         }
-    };
+    }
+}
 
-    let mut sum = 0;
-    let mut processing: bool = true;
-    for cap in re.captures_iter(&data) {
-        let matched = match cap.get(0) {
-            Some(m) => m.as_str(),
-            None => continue,
-        };
-        match matched {
-            m if m.starts_with("mul") => {
-                if processing {
-                    let num1 = match cap[1].parse::<i32>() {
-                        Ok(n) => n,
-                        Err(_) => continue,
-                    };
-                    let num2 = match cap[2].parse::<i32>() {
-                        Ok(n) => n,
-                        Err(_) => continue,
-                    };
-                    sum += num1 * num2;
+/// The byte-at-a-time state machine behind `Tokenizer`.
+struct Scanner {
+    state: State,
+    /// Offset of the next byte to be read.
+    offset: u64,
+    /// Offset where the current candidate instruction started.
+    start: u64,
+}
+
+impl Scanner {
+    /// Feeds one byte, returning an instruction if it completes one.
+    fn step(&mut self, byte: u8) -> Option<Instruction> {
+        const MUL: &[u8] = b"mul(";
+        const DONT: &[u8] = b"don't()";
+
+        let (next, done) = match (self.state, byte) {
+            (State::Mul(n), b) if n < MUL.len() && MUL[n] == b => {
+                if n + 1 == MUL.len() {
+                    (State::First(0, 0), None)
+                } else {
+                    (State::Mul(n + 1), None)
+                }
+            }
+            (State::Do(2), b'(') => (State::DoOpen, None),
+            (State::DoOpen, b')') => (State::Idle, Some(Instruction::Do)),
+            (State::Do(n), b) if DONT[n] == b => {
+                if n + 1 == DONT.len() {
+                    (State::Idle, Some(Instruction::Dont))
+                } else {
+                    (State::Do(n + 1), None)
                 }
             }
-            "do()" => {
-                processing = true;
+            (State::First(v, d), b'0'..=b'9') if d < MAX_DIGITS => {
+                (State::First(v * 10 + (byte - b'0') as u32, d + 1), None)
             }
-            "don't()" => {
-                processing = false;
+            (State::First(a, d), b',') if d > 0 => (State::Second(a, 0, 0), None),
+            (State::Second(a, v, d), b'0'..=b'9') if d < MAX_DIGITS => {
+                (State::Second(a, v * 10 + (byte - b'0') as u32, d + 1), None)
             }
-            _ => {}
+            (State::Second(a, b, d), b')') if d > 0 => (State::Idle, Some(Instruction::Mul(a, b))),
+            _ => (self.restart(byte), None),
         };
+
+        self.state = next;
+        self.offset += 1;
+        done
     }
 
-    println!("Day 3 Part 2: {:?}", sum);
+    /// The state for `byte` when no instruction is in progress.
+    fn restart(&mut self, byte: u8) -> State {
+        self.start = self.offset;
+        match byte {
+            b'm' => State::Mul(1),
+            b'd' => State::Do(1),
+            _ => State::Idle,
+        }
+    }
+}
 
-    Ok(())
+impl<R: BufRead> Iterator for Tokenizer<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<io::Result<Token>> {
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok([]) => return None,
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+
+            // Stop at the first completed instruction and leave the rest of
+            // the buffer for the next call.
+            let mut found = None;
+            let mut used = buf.len();
+            for (i, &byte) in buf.iter().enumerate() {
+                if let Some(instruction) = self.scanner.step(byte) {
+                    found = Some(Token {
+                        offset: self.scanner.start,
+                        instruction,
+                    });
+                    used = i + 1;
+                    break;
+                }
+            }
+            self.reader.consume(used);
+            if let Some(token) = found {
+                return Some(Ok(token));
+            }
+        }
+    }
 }
 
-fn read_from_file(file_path: &str) -> Result<String, io::Error> {
-    // Read the data from the specified file path
-    let data = match std::fs::read_to_string(file_path) {
-        Ok(d) => d,
-        Err(e) => return Err(e),
-    };
-    Ok(data)
+/// Runs the instructions, returning the sum of every `mul` and the sum of
+/// those seen while enabled. Instructions start enabled.
+fn interpret(
+    tokens: impl Iterator<Item = io::Result<Token>>,
+    trace: bool,
+) -> Result<(u64, u64), io::Error> {
+    let (mut all, mut enabled_sum) = (0u64, 0u64);
+    let mut enabled = true;
+
+    for token in tokens {
+        let token = token?;
+        match token.instruction {
+            Instruction::Mul(a, b) => {
+                let product = a as u64 * b as u64;
+                all += product;
+                if enabled {
+                    enabled_sum += product;
+                }
+                if trace {
+                    let counted = if enabled { "counted" } else { "skipped" };
+                    println!(
+                        "{:>10}  mul({},{}) = {} {}",
+                        token.offset, a, b, product, counted
+                    );
+                }
+            }
+            Instruction::Do | Instruction::Dont => {
+                enabled = token.instruction == Instruction::Do;
+                if trace {
+                    let text = if enabled { "do()" } else { "don't()" };
+                    println!("{:>10}  {}", token.offset, text);
+                }
+            }
+        }
+    }
+
+    Ok((all, enabled_sum))
 }
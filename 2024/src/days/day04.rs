@@ -1,137 +1,206 @@
 use std::io::{self};
+use std::path::PathBuf;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_from_file("day4/input.txt") {
-        Ok(d) => d,
-        Err(e) => {
-            return Err(Box::new(e));
-        }
-    };
+use aoc_core::grid::Grid;
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The word search.
+pub const INPUT: Shape = Shape::Grid("XMAS");
+
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day04_options")]
+pub struct Options {
+    /// Day 4: also search for these words, e.g. `--words XMAS,SAM`
+    #[clap(long, value_delimiter = ',')]
+    pub words: Vec<String>,
+
+    /// Day 4: also match the stencil in this file in every rotation and
+    /// reflection; `.` in the stencil matches any letter
+    #[clap(long)]
+    pub stencil: Option<PathBuf>,
+
+    /// Day 4: list every match of --words and --stencil, not just the count
+    #[clap(long)]
+    pub list_matches: bool,
+}
+
+/// The X-MAS cross from part 2. The other three ways of writing it are its
+/// rotations.
+const X_MAS: &str = "\
+M.S
+.A.
+M.S";
+
+/// Ceres search. Using `day4/sample.txt` gives 18 and 9.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day04;
+    let data = input::read("day4/input.txt")?;
+    let puzzle = Grid::parse(&data)?;
 
-    // Input is a word search, where the goal is to find all instances of XMAS in the puzzle
+    answer(1, find_words(&puzzle, &["XMAS"]).len());
 
-    // First, we need to parse the data into a 2D vector
-    let mut puzzle: Vec<Vec<char>> = Vec::new();
-    for line in data.lines() {
-        let mut row = Vec::new();
-        for c in line.chars() {
-            row.push(c);
+    let x_mas = Stencil::parse(X_MAS)?;
+    answer(2, x_mas.find(&puzzle).len());
+
+    if !options.words.is_empty() {
+        let matches = find_words(&puzzle, &options.words);
+        println!("Day 4 words found: {}", matches.len());
+        if options.list_matches {
+            for m in &matches {
+                println!("  {} at ({}, {}) going {}", m.word, m.x, m.y, m.direction);
+            }
         }
-        puzzle.push(row);
     }
 
-    // Next, we need to find all instances of XMAS in the puzzle
-    // We'll use a graph search when we find the X, and search in all 8 directions
-    // for the rest of the word
-    let word = "XMAS";
-    let mut instances = 0;
-    for i in 0..puzzle.len() {
-        for j in 0..puzzle[i].len() {
-            if puzzle[i][j] == 'X' {
-                // Search in all 8 directions for the rest of the word
-                for (dx, dy) in &[
-                    (0, 1),
-                    (1, 0),
-                    (1, 1),
-                    (1, -1),
-                    (0, -1),
-                    (-1, 0),
-                    (-1, -1),
-                    (-1, 1),
-                ] {
-                    let mut found = true;
-                    for k in 1..word.len() {
-                        let new_i = i as i32 + k as i32 * dx;
-                        let new_j = j as i32 + k as i32 * dy;
-                        if new_i < 0
-                            || new_i >= puzzle.len() as i32
-                            || new_j < 0
-                            || new_j >= puzzle[i].len() as i32
-                        {
-                            found = false;
-                            break;
-                        }
-                        let c = match word.chars().nth(k) {
-                            Some(c) => c,
-                            None => {
-                                found = false;
-                                break;
-                            }
-                        };
-                        if puzzle[new_i as usize][new_j as usize] != c {
-                            found = false;
-                            break;
-                        }
-                    }
-                    if found {
-                        instances += 1;
-                    }
-                }
+    if let Some(path) = &options.stencil {
+        let stencil = Stencil::parse(&std::fs::read_to_string(path)?)?;
+        let matches = stencil.find(&puzzle);
+        println!("Day 4 stencil matches: {}", matches.len());
+        if options.list_matches {
+            for (x, y, orientation) in &matches {
+                println!("  at ({}, {}) in orientation {}", x, y, orientation);
             }
         }
     }
 
-    println!("Day 4 Part 1: {:?}", instances);
-
-    // For part 2, need to find all crossing MAS instances so:
-    // M . S
-    // . A .
-    // M . S
-    // The above counts for one instance, so we need to find all A
-    // instances and look for two M-S pairs diagonal to it
-    let mut instances = 0;
-    for a in 1..puzzle.len() - 1 {
-        for b in 1..puzzle[a].len() - 1 {
-            if puzzle[a][b] == 'A' {
-                // Configuration one is S . M above and S . M below, check for that specifically
-                if puzzle[a - 1][b - 1] == 'S'
-                    && puzzle[a - 1][b + 1] == 'M'
-                    && puzzle[a + 1][b - 1] == 'S'
-                    && puzzle[a + 1][b + 1] == 'M'
-                {
-                    instances += 1;
-                }
+    Ok(())
+}
 
-                // Configuration two is M . M above and S . S below, check for that specifically
-                if puzzle[a - 1][b - 1] == 'M'
-                    && puzzle[a - 1][b + 1] == 'M'
-                    && puzzle[a + 1][b - 1] == 'S'
-                    && puzzle[a + 1][b + 1] == 'S'
-                {
-                    instances += 1;
-                }
+/// Compass directions as `(name, dx, dy)`, with y growing down the page.
+const DIRECTIONS: [(&str, i64, i64); 8] = [
+    ("E", 1, 0),
+    ("SE", 1, 1),
+    ("S", 0, 1),
+    ("SW", -1, 1),
+    ("W", -1, 0),
+    ("NW", -1, -1),
+    ("N", 0, -1),
+    ("NE", 1, -1),
+];
 
-                // Configuration three is S . S above and M . M below, check for that specifically
-                if puzzle[a - 1][b - 1] == 'S'
-                    && puzzle[a - 1][b + 1] == 'S'
-                    && puzzle[a + 1][b - 1] == 'M'
-                    && puzzle[a + 1][b + 1] == 'M'
-                {
-                    instances += 1;
-                }
+struct WordMatch<'a> {
+    word: &'a str,
+    x: usize,
+    y: usize,
+    direction: &'static str,
+}
+
+/// Every place a word reads in a straight line, starting from its first
+/// letter, in any of the eight directions.
+fn find_words<'a, S: AsRef<str>>(puzzle: &Grid<char>, words: &'a [S]) -> Vec<WordMatch<'a>> {
+    let words: Vec<(&str, Vec<char>)> = words
+        .iter()
+        .map(|w| (w.as_ref(), w.as_ref().chars().collect()))
+        .filter(|(_, letters): &(&str, Vec<char>)| !letters.is_empty())
+        .collect();
 
-                // Configuration four is M . S above and M . S below, check for that specifically
-                if puzzle[a - 1][b - 1] == 'M'
-                    && puzzle[a - 1][b + 1] == 'S'
-                    && puzzle[a + 1][b - 1] == 'M'
-                    && puzzle[a + 1][b + 1] == 'S'
-                {
-                    instances += 1;
+    let mut matches = Vec::new();
+    for (x, y) in puzzle.positions() {
+        for (word, letters) in &words {
+            if puzzle[(x, y)] != letters[0] {
+                continue;
+            }
+            for &(direction, dx, dy) in &DIRECTIONS {
+                let reads = letters.iter().enumerate().skip(1).all(|(k, &c)| {
+                    let k = k as i64;
+                    puzzle.get(x as i64 + k * dx, y as i64 + k * dy) == Some(&c)
+                });
+                if reads {
+                    matches.push(WordMatch {
+                        word,
+                        x,
+                        y,
+                        direction,
+                    });
                 }
             }
         }
     }
+    matches
+}
 
-    println!("Day 4 Part 2: {:?}", instances);
-
-    Ok(())
+/// A rectangular pattern of letters where `None` matches anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stencil {
+    rows: Vec<Vec<Option<char>>>,
 }
 
-fn read_from_file(file_path: &str) -> Result<String, io::Error> {
-    // Read the data from the specified file path
-    let data = match std::fs::read_to_string(file_path) {
-        Ok(d) => d,
-        Err(e) => return Err(e),
-    };
-    Ok(data)
+impl Stencil {
+    fn parse(text: &str) -> Result<Stencil, io::Error> {
+        let rows: Vec<Vec<Option<char>>> = text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim().chars().map(|c| (c != '.').then_some(c)).collect())
+            .collect();
+        let width = rows.first().map_or(0, |r| r.len());
+        if width == 0 || rows.iter().any(|r| r.len() != width) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stencil must be a non-empty rectangle",
+            ));
+        }
+        Ok(Stencil { rows })
+    }
+
+    /// A quarter turn clockwise.
+    fn rotate(&self) -> Stencil {
+        let height = self.rows.len();
+        let width = self.rows[0].len();
+        Stencil {
+            rows: (0..width)
+                .map(|c| (0..height).rev().map(|r| self.rows[r][c]).collect())
+                .collect(),
+        }
+    }
+
+    fn reflect(&self) -> Stencil {
+        Stencil {
+            rows: self
+                .rows
+                .iter()
+                .map(|r| r.iter().rev().copied().collect())
+                .collect(),
+        }
+    }
+
+    /// The distinct rotations and reflections, so a symmetric stencil isn't
+    /// counted more than once at the same place.
+    fn orientations(&self) -> Vec<Stencil> {
+        let mut all: Vec<Stencil> = Vec::new();
+        for start in [self.clone(), self.reflect()] {
+            let mut s = start;
+            for _ in 0..4 {
+                if !all.contains(&s) {
+                    all.push(s.clone());
+                }
+                s = s.rotate();
+            }
+        }
+        all
+    }
+
+    fn fits_at(&self, puzzle: &Grid<char>, x: usize, y: usize) -> bool {
+        self.rows.iter().enumerate().all(|(dy, row)| {
+            row.iter().enumerate().all(|(dx, cell)| match cell {
+                None => puzzle.get((x + dx) as i64, (y + dy) as i64).is_some(),
+                Some(c) => puzzle.get((x + dx) as i64, (y + dy) as i64) == Some(c),
+            })
+        })
+    }
+
+    /// Top-left corner and orientation index of every match.
+    fn find(&self, puzzle: &Grid<char>) -> Vec<(usize, usize, usize)> {
+        let orientations = self.orientations();
+        puzzle
+            .positions()
+            .flat_map(|(x, y)| {
+                orientations
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, s)| s.fits_at(puzzle, x, y))
+                    .map(move |(i, _)| (x, y, i))
+            })
+            .collect()
+    }
 }
@@ -1,8 +1,13 @@
 use std::collections::{HashMap, HashSet};
-use std::io::{self};
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_from_file("day5/input.txt") {
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The page ordering rules, then the updates.
+pub const INPUT: Shape = Shape::Sections(&[Shape::Lines("<uint>|<uint>"), Shape::Lines("<ints,>")]);
+
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let data = match input::read("day5/input.txt") {
         Ok(d) => d,
         Err(e) => {
             return Err(Box::new(e));
@@ -76,21 +81,12 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    println!("Day 5 Part 1: {:?}", sum);
-    println!("Day 5 Part 2: {:?}", sum2);
+    answer(1, sum);
+    answer(2, sum2);
 
     Ok(())
 }
 
-fn read_from_file(file_path: &str) -> Result<String, io::Error> {
-    // Read the data from the specified file path
-    let data = match std::fs::read_to_string(file_path) {
-        Ok(d) => d,
-        Err(e) => return Err(e),
-    };
-    Ok(data)
-}
-
 fn in_right_order(
     update: Vec<i32>,
     befores: HashMap<i32, HashSet<i32>>,
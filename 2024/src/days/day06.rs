@@ -1,8 +1,14 @@
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+use aoc_core::visualize::Renderer;
 use std::collections::{HashMap, HashSet};
-use std::{fs, io};
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_from_file("day6/sample.txt") {
+/// The lab map with the guard facing up.
+pub const INPUT: Shape = Shape::Grid(".#^");
+
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let vis = &args.visualize;
+    let data = match input::read("day6/sample.txt") {
         Ok(d) => d,
         Err(e) => return Err(e.into()),
     };
@@ -27,7 +33,7 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         (i, j, on_board) = has_guard(grid.clone());
     }
 
-    println!("Day 6 Part 1: {:?}", set.len());
+    answer(1, set.len());
 
     let mut grid: Vec<Vec<char>> = Vec::new();
     for line in data.lines() {
@@ -38,11 +44,62 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         grid.push(row);
     }
 
+    if vis.visualize {
+        let mut renderer = Renderer::new(vis.fps);
+        walk_guard(&mut grid, |grid, step| {
+            renderer.frame(grid, &format!("step {}", step))
+        });
+    }
+
     Ok(())
 }
 
-fn read_from_file(file_path: &str) -> io::Result<String> {
-    fs::read_to_string(file_path)
+/// Walks the guard one tile per tick, marking visited tiles with `X` and
+/// calling `on_tick` with the grid and step count after each one. Stops when the
+/// guard leaves the map or revisits a tile facing the same way.
+fn walk_guard<F>(grid: &mut [Vec<char>], mut on_tick: F)
+where
+    F: FnMut(&[Vec<char>], usize),
+{
+    let (mut i, mut j, on_board) = has_guard(grid.to_vec());
+    if !on_board {
+        return;
+    }
+
+    let mut seen = HashSet::new();
+    let mut step = 0;
+    on_tick(grid, step);
+    while seen.insert((i, j, grid[i][j])) {
+        let c = grid[i][j];
+        let (di, dj) = match c {
+            '^' => (-1, 0),
+            '>' => (0, 1),
+            'v' => (1, 0),
+            _ => (0, -1),
+        };
+        let (ni, nj) = (i as i32 + di, j as i32 + dj);
+        if ni < 0 || nj < 0 || ni >= grid.len() as i32 || nj >= grid[i].len() as i32 {
+            grid[i][j] = 'X';
+            on_tick(grid, step + 1);
+            return;
+        }
+
+        let (ni, nj) = (ni as usize, nj as usize);
+        if grid[ni][nj] == '#' {
+            grid[i][j] = match c {
+                '^' => '>',
+                '>' => 'v',
+                'v' => '<',
+                _ => '^',
+            };
+        } else {
+            grid[i][j] = 'X';
+            grid[ni][nj] = c;
+            (i, j) = (ni, nj);
+        }
+        step += 1;
+        on_tick(grid, step);
+    }
 }
 
 fn has_guard(grid: Vec<Vec<char>>) -> (usize, usize, bool) {
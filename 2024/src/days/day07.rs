@@ -1,68 +1,319 @@
-use std::{fs, io};
+use std::fmt;
+use std::str::FromStr;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_from_file("day7/input.txt") {
-        Ok(d) => d,
-        Err(e) => return Err(e.into()),
-    };
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
 
-    let mut sum: u64 = 0;
-    let mut sum2: u64 = 0;
-    for line in data.lines() {
-        // Split on the ":"
-        let (total, vals) = line.split_once(":").unwrap();
+/// A test value and its numbers per line.
+pub const INPUT: Shape = Shape::Lines("<uint>: <ints>");
 
-        // total is an i32, vals is a space separated list of i32s
-        let total = match total.parse::<u64>() {
-            Ok(t) => t,
-            Err(e) => return Err(e.into()),
-        };
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day07_options")]
+pub struct Options {
+    /// Day 7: also solve with this operator set, from `+`, `*`, `||`, `-` and
+    /// `/`, e.g. `--operators '+,*,-'`
+    #[clap(long, value_delimiter = ',')]
+    pub operators: Vec<Operator>,
+
+    /// Day 7: print an expression for each solvable equation, using the last
+    /// operator set solved
+    #[clap(long)]
+    pub show_expressions: bool,
+
+    /// Day 7: with --show-expressions, print every expression and how many
+    /// there are instead of just the first
+    #[clap(long)]
+    pub all_solutions: bool,
+}
+
+/// Expressions are evaluated left to right, ignoring precedence. Values stay
+/// non-negative, so `-` can't take a value below zero, and `/` only divides
+/// exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Mul,
+    Concat,
+    Sub,
+    Div,
+}
+
+impl Operator {
+    fn apply(self, value: u64, operand: u64) -> Option<u64> {
+        match self {
+            Operator::Add => value.checked_add(operand),
+            Operator::Mul => value.checked_mul(operand),
+            Operator::Concat => value
+                .checked_mul(concat_shift(operand))
+                .and_then(|v| v.checked_add(operand)),
+            Operator::Sub => value.checked_sub(operand),
+            Operator::Div => {
+                (operand != 0 && value.is_multiple_of(operand)).then(|| value / operand)
+            }
+        }
+    }
 
-        let vals = vals.split_whitespace();
-        let mut all_vals = Vec::new();
-        for val in vals {
-            match val.parse::<u64>() {
-                Ok(v) => all_vals.push(v),
-                Err(e) => return Err(e.into()),
+    /// The value before `operand` was applied, if `target` can be reached at
+    /// all. Only `*` by zero has no single answer; the solver handles it.
+    fn unapply(self, target: u64, operand: u64) -> Option<u64> {
+        match self {
+            Operator::Add => target.checked_sub(operand),
+            Operator::Mul => {
+                (operand != 0 && target.is_multiple_of(operand)).then(|| target / operand)
+            }
+            Operator::Concat => {
+                let shift = concat_shift(operand);
+                (target % shift == operand).then(|| target / shift)
+            }
+            Operator::Sub => target.checked_add(operand),
+            Operator::Div => {
+                if operand == 0 {
+                    None
+                } else {
+                    target.checked_mul(operand)
+                }
             }
         }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Mul => "*",
+            Operator::Concat => "||",
+            Operator::Sub => "-",
+            Operator::Div => "/",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl FromStr for Operator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Operator, String> {
+        match s.trim() {
+            "+" => Ok(Operator::Add),
+            "*" => Ok(Operator::Mul),
+            "||" => Ok(Operator::Concat),
+            "-" => Ok(Operator::Sub),
+            "/" => Ok(Operator::Div),
+            other => Err(format!("unknown operator `{}`", other)),
+        }
+    }
+}
+
+/// `10^digits(operand)`, so that `value * shift + operand` concatenates.
+fn concat_shift(operand: u64) -> u64 {
+    10_u64.pow(operand.checked_ilog10().unwrap_or(0) + 1)
+}
+
+const PART1: [Operator; 2] = [Operator::Add, Operator::Mul];
+const PART2: [Operator; 3] = [Operator::Add, Operator::Mul, Operator::Concat];
+
+/// Bridge repair. Using `day7/sample.txt` gives 3749 and 11387.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day07;
+    let data = input::read("day7/input.txt")?;
+    let equations = parse_equations(&data)?;
+
+    answer(1, calibration(&equations, &PART1));
+    answer(2, calibration(&equations, &PART2));
+
+    let operators: &[Operator] = if options.operators.is_empty() {
+        &PART2
+    } else {
+        let total = calibration(&equations, &options.operators);
+        println!("Day 7 with {}: {:?}", symbols(&options.operators), total);
+        &options.operators
+    };
 
-        if match_operands_1(total, &all_vals.as_slice()) {
-            sum += total;
-        } else if match_operands(total, &all_vals.as_slice()) {
-            sum2 += total;
+    if options.show_expressions {
+        for equation in &equations {
+            show_solutions(equation, operators, options.all_solutions);
         }
     }
-    println!("Day 7 Part 1: {:?}", sum);
-    println!("Day 7 Part 2: {:?}", sum + sum2);
 
     Ok(())
 }
 
-fn read_from_file(file_path: &str) -> io::Result<String> {
-    fs::read_to_string(file_path)
+struct Equation {
+    target: u64,
+    operands: Vec<u64>,
 }
 
-fn match_operands(val: u64, operands: &[u64]) -> bool {
-    match operands {
-        [] => panic!("No operands"),
-        [last] => *last == val,
-        [rest @ .., last] => {
-            let mask = 10_u64.pow(last.ilog10() as u32 + 1);
-            (val % last == 0 && match_operands(val / last, rest))
-                || (val >= *last && match_operands(val - last, rest))
-                || (val % mask == *last && match_operands(val / mask, rest))
+fn parse_equations(data: &str) -> Result<Vec<Equation>, Box<dyn std::error::Error>> {
+    let mut equations = Vec::new();
+    for (n, line) in data
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+    {
+        let (target, operands) = line
+            .split_once(':')
+            .ok_or_else(|| format!("line {}: missing `:`", n + 1))?;
+        let operands = operands
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<u64>, _>>()?;
+        if operands.is_empty() {
+            return Err(format!("line {}: no operands", n + 1).into());
         }
+        equations.push(Equation {
+            target: target.trim().parse()?,
+            operands,
+        });
     }
+    Ok(equations)
 }
 
-fn match_operands_1(val: u64, operands: &[u64]) -> bool {
-    match operands {
-        [] => panic!("No operands"),
-        [last] => *last == val,
-        [rest @ .., last] => {
-            (val % last == 0 && match_operands(val / last, rest))
-                || (val >= *last && match_operands(val - last, rest))
+/// Sum of the targets of every equation with at least one solution.
+fn calibration(equations: &[Equation], operators: &[Operator]) -> u64 {
+    equations
+        .iter()
+        .filter(|eq| first_solution(eq, operators).is_some())
+        .map(|eq| eq.target)
+        .sum()
+}
+
+fn first_solution(equation: &Equation, operators: &[Operator]) -> Option<Vec<Operator>> {
+    let mut found = None;
+    solve(equation, operators, &mut |ops| {
+        found = Some(ops.to_vec());
+        false
+    });
+    found
+}
+
+fn show_solutions(equation: &Equation, operators: &[Operator], all: bool) {
+    if !all {
+        if let Some(ops) = first_solution(equation, operators) {
+            println!("{} = {}", equation.target, expression(equation, &ops));
         }
+        return;
+    }
+
+    let mut solutions = Vec::new();
+    solve(equation, operators, &mut |ops| {
+        solutions.push(expression(equation, ops));
+        true
+    });
+    if !solutions.is_empty() {
+        let plural = if solutions.len() == 1 { "" } else { "s" };
+        println!(
+            "{}: {} solution{}",
+            equation.target,
+            solutions.len(),
+            plural
+        );
+        for s in solutions {
+            println!("  {} = {}", equation.target, s);
+        }
+    }
+}
+
+/// Calls `visit` with the operators of each solution until it returns false.
+///
+/// Works backwards from the target: the last operand must have been applied
+/// to whatever `unapply` gives, which prunes most branches straight away.
+fn solve(equation: &Equation, operators: &[Operator], visit: &mut dyn FnMut(&[Operator]) -> bool) {
+    let mut chosen = Vec::with_capacity(equation.operands.len());
+    solve_from(
+        equation.target,
+        &equation.operands,
+        operators,
+        &mut chosen,
+        visit,
+    );
+}
+
+/// `chosen` holds the operators after `operands`, last one first.
+fn solve_from(
+    target: u64,
+    operands: &[u64],
+    operators: &[Operator],
+    chosen: &mut Vec<Operator>,
+    visit: &mut dyn FnMut(&[Operator]) -> bool,
+) -> bool {
+    let (&last, rest) = match operands.split_last() {
+        Some(split) => split,
+        None => return true,
+    };
+    if rest.is_empty() {
+        return last != target || visit(&in_order(&[], chosen));
     }
+
+    for &op in operators {
+        chosen.push(op);
+        let go_on = if op == Operator::Mul && last == 0 {
+            // Anything times zero is zero, so the value before it can be
+            // anything the earlier operands evaluate to.
+            target != 0
+                || each_evaluation(rest[0], &rest[1..], operators, &mut Vec::new(), &mut |p| {
+                    visit(&in_order(p, chosen))
+                })
+        } else {
+            match op.unapply(target, last) {
+                Some(prev) => solve_from(prev, rest, operators, chosen, visit),
+                None => true,
+            }
+        };
+        chosen.pop();
+        if !go_on {
+            return false;
+        }
+    }
+    true
+}
+
+/// Calls `visit` with every operator sequence that evaluates without going
+/// negative, overflowing or dividing unevenly.
+fn each_evaluation(
+    value: u64,
+    operands: &[u64],
+    operators: &[Operator],
+    prefix: &mut Vec<Operator>,
+    visit: &mut dyn FnMut(&[Operator]) -> bool,
+) -> bool {
+    let Some((&next, rest)) = operands.split_first() else {
+        return visit(prefix);
+    };
+    for &op in operators {
+        if let Some(v) = op.apply(value, next) {
+            prefix.push(op);
+            let go_on = each_evaluation(v, rest, operators, prefix, visit);
+            prefix.pop();
+            if !go_on {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn in_order(prefix: &[Operator], reversed_suffix: &[Operator]) -> Vec<Operator> {
+    prefix
+        .iter()
+        .chain(reversed_suffix.iter().rev())
+        .copied()
+        .collect()
+}
+
+/// e.g. `81 + 40 * 27`
+fn expression(equation: &Equation, operators: &[Operator]) -> String {
+    let mut s = equation.operands[0].to_string();
+    for (op, operand) in operators.iter().zip(&equation.operands[1..]) {
+        s += &format!(" {} {}", op, operand);
+    }
+    s
+}
+
+fn symbols(operators: &[Operator]) -> String {
+    operators
+        .iter()
+        .map(|op| op.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
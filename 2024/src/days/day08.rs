@@ -1,141 +1,186 @@
 use std::collections::{HashMap, HashSet};
-use std::{fs, io};
-
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_from_file("day8/input.txt") {
-        Ok(d) => d,
-        Err(e) => return Err(e.into()),
-    };
-
-    let mut grid: Vec<Vec<char>> = Vec::new();
-    let mut nodes: HashMap<char, Vec<(usize, usize)>> = HashMap::new();
-    // Create a hashmap of all the non-'.' values. There are multiple for each character
-    for line in data.lines() {
-        let mut row = Vec::new();
-        for c in line.chars() {
-            if c != '.' {
-                if !nodes.contains_key(&c.clone()) {
-                    nodes.insert(c.clone(), Vec::new());
-                }
-                nodes
-                    .get_mut(&c.clone())
-                    .unwrap()
-                    .push((grid.len(), row.len()));
-            }
-            row.push(c);
-        }
-        grid.push(row);
-    }
 
-    let (width, height) = (grid[0].len(), grid.len());
+use aoc_core::grid::Grid;
+use aoc_core::input::{self, Shape};
+use aoc_core::linalg::gcd;
+use aoc_core::runner::answer;
 
-    let mut new_nodes = HashSet::<(usize, usize)>::new();
-    for (_node, locations) in nodes.iter_mut() {
-        locations.sort();
+/// The antenna map; any letter or digit is an antenna.
+pub const INPUT: Shape = Shape::Grid("");
 
-        for i in 0..locations.len() - 1 {
-            for j in i + 1..locations.len() {
-                // 1. Want to extend the line by locations_i, locations_j in both directions one time and add
-                // the new locations to new_nodes. Make sure to only add one time in each direction
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day08_options")]
+pub struct Options {
+    /// Day 8: also count antinodes at only these harmonics, where the k-th
+    /// harmonic is k antenna gaps beyond either antenna (0 is the antennas
+    /// themselves), e.g. `--harmonics 1,2`
+    #[clap(long, value_delimiter = ',')]
+    pub harmonics: Vec<i64>,
 
-                let (new_i, new_j) = next_point(
-                    locations[i].0 as i32,
-                    locations[i].1 as i32,
-                    locations[j].0 as i32,
-                    locations[j].1 as i32,
-                    1,
-                );
-                if new_i >= 0 && new_i < height as i32 && new_j >= 0 && new_j < width as i32 {
-                    new_nodes.insert((new_i as usize, new_j as usize));
-                }
-                let (new_i, new_j) = next_point(
-                    locations[i].0 as i32,
-                    locations[i].1 as i32,
-                    locations[j].0 as i32,
-                    locations[j].1 as i32,
-                    -1,
-                );
-                if new_i >= 0 && new_i < height as i32 && new_j >= 0 && new_j < width as i32 {
-                    new_nodes.insert((new_i as usize, new_j as usize));
-                }
-            }
+    /// Day 8: also count antinodes at least this many grid steps beyond the
+    /// nearer antenna
+    #[clap(long)]
+    pub min_distance: Option<i64>,
+
+    /// Day 8: also count antinodes at most this many grid steps beyond the
+    /// nearer antenna
+    #[clap(long)]
+    pub max_distance: Option<i64>,
+
+    /// Day 8: print the antinodes over the antenna map
+    #[clap(long)]
+    pub show_antinodes: bool,
+}
+
+/// Resonant collinearity. Using `day8/sample.txt` gives 14 and 34, and
+/// `day8/sample2.txt` gives 9 for part 2.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day08;
+    let data = input::read("day8/input.txt")?;
+    let grid = Grid::parse(&data)?;
+    let antennas = antennas(&grid);
+
+    let mut rules = vec![(Some(1), Resonance::part1()), (Some(2), Resonance::part2())];
+    if !options.harmonics.is_empty()
+        || options.min_distance.is_some()
+        || options.max_distance.is_some()
+    {
+        rules.push((
+            None,
+            Resonance {
+                harmonics: (!options.harmonics.is_empty()).then(|| options.harmonics.clone()),
+                min_distance: options.min_distance.unwrap_or(0),
+                max_distance: options.max_distance,
+            },
+        ));
+    }
+
+    for (part, rule) in rules {
+        let found = antinodes(&grid, &antennas, &rule);
+        match part {
+            Some(part) => answer(part, found.len()),
+            None => println!("Day 8 custom resonance: {}", found.len()),
+        }
+        if options.show_antinodes {
+            print_antinodes(&grid, &found);
+        }
+    }
+
+    Ok(())
+}
+
+/// Antenna positions as `(x, y)`, grouped by frequency.
+fn antennas(grid: &Grid<char>) -> HashMap<char, Vec<(i64, i64)>> {
+    let mut antennas: HashMap<char, Vec<(i64, i64)>> = HashMap::new();
+    for (x, y) in grid.positions() {
+        let c = grid[(x, y)];
+        if c != '.' {
+            antennas.entry(c).or_default().push((x as i64, y as i64));
         }
     }
+    antennas
+}
 
-    println!("day 8 part 1: {:?}", new_nodes.len());
+/// Which points on the line through a pair of antennas are antinodes. Points
+/// are measured in grid steps beyond the nearer antenna; points between the
+/// antennas count as 0.
+#[derive(Debug, Clone)]
+struct Resonance {
+    /// Only points exactly k antenna gaps beyond an antenna, for these k.
+    /// `None` allows every grid point on the line.
+    harmonics: Option<Vec<i64>>,
+    min_distance: i64,
+    max_distance: Option<i64>,
+}
 
-    let mut new_nodes = HashSet::<(usize, usize)>::new();
-    for (_node, locations) in nodes.iter_mut() {
-        locations.sort();
+impl Resonance {
+    /// One antenna is twice as far away as the other.
+    fn part1() -> Resonance {
+        Resonance {
+            harmonics: Some(vec![1]),
+            min_distance: 0,
+            max_distance: None,
+        }
+    }
 
-        for i in 0..locations.len() - 1 {
-            for j in i + 1..locations.len() {
-                // 1. Want to extend the line by locations_i, locations_j in both directions one time and add
-                // the new locations to new_nodes. Make sure to only add one time in each direction
+    /// Anywhere in line with the pair.
+    fn part2() -> Resonance {
+        Resonance {
+            harmonics: None,
+            min_distance: 0,
+            max_distance: None,
+        }
+    }
 
-                new_nodes.insert(locations[i]);
-                new_nodes.insert(locations[j]);
+    /// `t` is the point's position along the line in reduced steps, with the
+    /// antennas at 0 and `gap`.
+    fn accepts(&self, t: i64, gap: i64) -> bool {
+        let beyond = if t < 0 { -t } else { (t - gap).max(0) };
+        if beyond < self.min_distance || self.max_distance.is_some_and(|m| beyond > m) {
+            return false;
+        }
+        match &self.harmonics {
+            None => true,
+            Some(ks) => {
+                let outside = t <= 0 || t >= gap;
+                outside && beyond % gap == 0 && ks.contains(&(beyond / gap))
+            }
+        }
+    }
+}
 
-                let mut direction = 1;
-                let (mut new_i, mut new_j) = next_point(
-                    locations[i].0 as i32,
-                    locations[i].1 as i32,
-                    locations[j].0 as i32,
-                    locations[j].1 as i32,
-                    direction,
-                );
-                while new_i >= 0 && new_i < height as i32 && new_j >= 0 && new_j < width as i32 {
-                    new_nodes.insert((new_i as usize, new_j as usize));
-                    direction += 1;
-                    (new_i, new_j) = next_point(
-                        locations[i].0 as i32,
-                        locations[i].1 as i32,
-                        locations[j].0 as i32,
-                        locations[j].1 as i32,
-                        direction,
-                    );
-                }
-                direction = -1;
-                (new_i, new_j) = next_point(
-                    locations[i].0 as i32,
-                    locations[i].1 as i32,
-                    locations[j].0 as i32,
-                    locations[j].1 as i32,
-                    -1,
+fn antinodes(
+    grid: &Grid<char>,
+    antennas: &HashMap<char, Vec<(i64, i64)>>,
+    rule: &Resonance,
+) -> HashSet<(i64, i64)> {
+    let mut found = HashSet::new();
+    for locations in antennas.values() {
+        for (i, &a) in locations.iter().enumerate() {
+            for &b in &locations[i + 1..] {
+                found.extend(
+                    walk_line(grid, a, b)
+                        .filter(|&(t, _, gap)| rule.accepts(t, gap))
+                        .map(|(_, p, _)| p),
                 );
-                while new_i >= 0 && new_i < height as i32 && new_j >= 0 && new_j < width as i32 {
-                    new_nodes.insert((new_i as usize, new_j as usize));
-                    direction -= 1;
-                    (new_i, new_j) = next_point(
-                        locations[i].0 as i32,
-                        locations[i].1 as i32,
-                        locations[j].0 as i32,
-                        locations[j].1 as i32,
-                        direction,
-                    );
-                }
             }
         }
     }
-
-    println!("day 8 part 2: {:?}", new_nodes.len());
-
-    Ok(())
+    found
 }
 
-fn read_from_file(file_path: &str) -> io::Result<String> {
-    fs::read_to_string(file_path)
+/// Every grid point on the line through `a` and `b`. The step is the antenna
+/// delta divided by its gcd, so points between whole multiples of the delta
+/// aren't skipped. Yields each point's position `t` in steps from `a`, along
+/// with the number of steps from `a` to `b`.
+fn walk_line(
+    grid: &Grid<char>,
+    a: (i64, i64),
+    b: (i64, i64),
+) -> impl Iterator<Item = (i64, (i64, i64), i64)> + '_ {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let gap = gcd(dx as i128, dy as i128) as i64;
+    let step = (dx / gap, dy / gap);
+
+    [1, -1].into_iter().flat_map(move |dir: i64| {
+        (0..)
+            .map(move |n: i64| {
+                let t = if dir > 0 { n } else { -n - 1 };
+                (t, (a.0 + t * step.0, a.1 + t * step.1), gap)
+            })
+            .take_while(|&(_, (x, y), _)| grid.get(x, y).is_some())
+    })
 }
 
-fn next_point(i0: i32, j0: i32, i1: i32, j1: i32, direction: i32) -> (i32, i32) {
-    let dx = i1 - i0;
-    let dy = j1 - j0;
-    if direction > 0 {
-        let next_i = i1 + (direction * dx);
-        let next_j = j1 + (direction * dy);
-        return (next_i, next_j);
+/// The map with `#` on every antinode that isn't covered by an antenna.
+fn print_antinodes(grid: &Grid<char>, antinodes: &HashSet<(i64, i64)>) {
+    for y in 0..grid.height() {
+        let row: String = (0..grid.width())
+            .map(|x| match grid[(x, y)] {
+                '.' if antinodes.contains(&(x as i64, y as i64)) => '#',
+                c => c,
+            })
+            .collect();
+        println!("{}", row);
     }
-    let next_i = i0 + (direction * dx);
-    let next_j = j0 + (direction * dy);
-    (next_i, next_j)
 }
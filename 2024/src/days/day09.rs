@@ -1,12 +1,42 @@
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
-use std::{fs, io};
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_from_file("day9/input.txt") {
-        Ok(d) => d,
-        Err(e) => return Err(e.into()),
-    };
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The disk map, a single long run of digits.
+pub const INPUT: Shape = Shape::Line("<uint>");
+
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day09_options")]
+pub struct Options {
+    /// Day 9: also compact the disk with this strategy and print its checksum
+    #[clap(long, value_enum)]
+    pub strategy: Option<Strategy>,
+
+    /// Day 9: print the disk layout before and after compacting (only drawn
+    /// for disks with at most ten files)
+    #[clap(long)]
+    pub show_disk: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Strategy {
+    /// Move single blocks from the end into the leftmost free block (part 1)
+    Blocks,
+    /// Move whole files into the leftmost span they fit in (part 2)
+    FirstFit,
+    /// Move whole files into the smallest span they fit in, leftmost on ties
+    BestFit,
+    /// Move whole files, lowest id first, into the rightmost span they fit in
+    DefragToEnd,
+}
+
+/// Disk fragmenter. Using `day9/sample.txt` gives 1928 and 2858, which the
+/// `blocks` and `first-fit` strategies reproduce from the disk map.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day09;
+    let data = input::read("day9/input.txt")?;
 
     // Convert data (a single string) to a Vec of i64s, there is no spacing it's a single long string
     let mut vals: Vec<usize> = Vec::new();
@@ -19,6 +49,63 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
 
     let disk = vals;
 
+    let part1 = part1(&disk);
+    let part2 = part2(&disk);
+    answer(1, part1);
+    answer(2, part2);
+
+    let strategies = match options.strategy {
+        Some(strategy) => vec![strategy],
+        None if options.show_disk => vec![Strategy::Blocks, Strategy::FirstFit],
+        None => vec![],
+    };
+
+    for strategy in strategies {
+        let checksum = compacted(&disk, strategy, options.show_disk).checksum();
+        println!("Day 9 {:?} checksum: {}", strategy, checksum);
+
+        // The block-level map is a cross-check on the arithmetic.
+        let expected = match strategy {
+            Strategy::Blocks => Some(part1),
+            Strategy::FirstFit => Some(part2),
+            _ => None,
+        };
+        if let Some(expected) = expected.filter(|&e| e != checksum) {
+            return Err(format!(
+                "day 9 {:?} disk map checksum {} differs from {}",
+                strategy, checksum, expected
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// The disk after compacting with `strategy`, drawn before and after with
+/// `show`.
+fn compacted(disk: &[usize], strategy: Strategy, show: bool) -> DiskMap {
+    let mut map = DiskMap::parse(disk);
+    if show {
+        print_layout(&map, strategy, "before");
+    }
+    map.compact(strategy);
+    if show {
+        print_layout(&map, strategy, "after");
+    }
+    map
+}
+
+fn print_layout(map: &DiskMap, strategy: Strategy, when: &str) {
+    match map.render() {
+        Some(layout) => println!("Day 9 {:?} disk {}: {}", strategy, when, layout),
+        None => println!("Day 9 {:?} disk {}: too many files to draw", strategy, when),
+    }
+}
+
+/// Part 1 without expanding the disk: walks the free spans from the left,
+/// filling each from the file at the right end.
+fn part1(disk: &[usize]) -> usize {
     // Start at the first free block and the last file.
     let mut left = 0;
     let mut right = disk.len() - 2 + disk.len() % 2;
@@ -51,14 +138,7 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
 
     // Account for any remaining file blocks left over.
     (checksum, _) = update(checksum, block, right, needed);
-    println!("Day 9 Part 1: {:?}", checksum);
-    println!("Day 9 Part 2: {:?}", part2(&disk));
-
-    Ok(())
-}
-
-fn read_from_file(file_path: &str) -> io::Result<String> {
-    fs::read_to_string(file_path)
+    checksum
 }
 
 const EXTRA: [usize; 10] = [0, 0, 1, 3, 6, 10, 15, 21, 28, 36];
@@ -69,7 +149,7 @@ fn update(checksum: usize, block: usize, index: usize, size: usize) -> (usize, u
     (checksum + id * extra, block + size)
 }
 
-pub fn part2(disk: &[usize]) -> usize {
+fn part2(disk: &[usize]) -> usize {
     let mut block = 0;
     let mut checksum = 0;
     let mut free: Vec<_> = (0..10).map(|_| MinHeap::with_capacity(1_000)).collect();
@@ -196,3 +276,188 @@ impl<K: Ord, V> MinHeap<K, V> {
         self.heap.peek().map(|w| (&w.key, &w.value))
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: usize,
+    len: usize,
+}
+
+/// The expanded disk: one entry per block holding the file id stored there,
+/// plus where each file and free span is. Slower than the checksum arithmetic
+/// above, but it shows what moved where.
+pub struct DiskMap {
+    blocks: Vec<Option<usize>>,
+    files: Vec<Span>,
+    free: Vec<Span>,
+}
+
+impl DiskMap {
+    /// Expands the dense format, where digits alternate file and free lengths.
+    pub fn parse(dense: &[usize]) -> DiskMap {
+        let mut blocks = Vec::new();
+        let mut files = Vec::new();
+        let mut free = Vec::new();
+
+        for (index, &len) in dense.iter().enumerate() {
+            let span = Span {
+                start: blocks.len(),
+                len,
+            };
+            if index % 2 == 0 {
+                blocks.extend(std::iter::repeat_n(Some(files.len()), len));
+                files.push(span);
+            } else {
+                blocks.extend(std::iter::repeat_n(None, len));
+                if len > 0 {
+                    free.push(span);
+                }
+            }
+        }
+
+        DiskMap {
+            blocks,
+            files,
+            free,
+        }
+    }
+
+    pub fn compact(&mut self, strategy: Strategy) {
+        match strategy {
+            Strategy::Blocks => self.compact_blocks(),
+            Strategy::FirstFit | Strategy::BestFit | Strategy::DefragToEnd => {
+                self.move_files(strategy)
+            }
+        }
+    }
+
+    /// Two pointers: the leftmost free block takes the rightmost file block
+    /// until they meet.
+    fn compact_blocks(&mut self) {
+        let (mut left, mut right) = (0, self.blocks.len());
+        loop {
+            while left < right && self.blocks[left].is_some() {
+                left += 1;
+            }
+            while left < right && self.blocks[right - 1].is_none() {
+                right -= 1;
+            }
+            if left + 1 >= right {
+                break;
+            }
+            self.blocks.swap(left, right - 1);
+        }
+    }
+
+    /// Tries each file once. Leftward strategies go highest id first and
+    /// defrag-to-end goes lowest id first, so the space a file leaves behind is
+    /// never somewhere a later file could move to and the free spans only
+    /// ever shrink. That also means a span too small for a file of some length
+    /// stays too small, so first-fit remembers where to start looking for each
+    /// length instead of scanning from the left every time.
+    fn move_files(&mut self, strategy: Strategy) {
+        let ids: Vec<usize> = match strategy {
+            Strategy::DefragToEnd => (0..self.files.len()).collect(),
+            _ => (0..self.files.len()).rev().collect(),
+        };
+        let longest = self.files.iter().map(|f| f.len).max().unwrap_or(0);
+        let mut first_fit_from = vec![0; longest + 1];
+
+        for id in ids {
+            let file = self.files[id];
+            if file.len == 0 {
+                continue;
+            }
+            let fits = |span: &Span| span.len >= file.len;
+
+            let chosen = match strategy {
+                Strategy::FirstFit => {
+                    let from = first_fit_from[file.len];
+                    let found = self.free[from..]
+                        .iter()
+                        .position(fits)
+                        .map_or(self.free.len(), |i| from + i);
+                    first_fit_from[file.len] = found;
+                    self.free
+                        .get(found)
+                        .filter(|span| span.start < file.start)
+                        .map(|_| found)
+                }
+                Strategy::BestFit => self
+                    .free
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, span)| fits(span) && span.start < file.start)
+                    .min_by_key(|(_, span)| (span.len, span.start))
+                    .map(|(index, _)| index),
+                _ => self
+                    .free
+                    .iter()
+                    .rposition(|span| fits(span) && span.start > file.start),
+            };
+            let Some(index) = chosen else {
+                continue;
+            };
+
+            let span = self.free[index];
+            let start = match strategy {
+                Strategy::DefragToEnd => span.start + span.len - file.len,
+                _ => span.start,
+            };
+            self.blocks[file.start..file.start + file.len].fill(None);
+            self.blocks[start..start + file.len].fill(Some(id));
+            self.files[id].start = start;
+
+            // Used-up spans are left in place, empty, so indices stay valid.
+            let rest = &mut self.free[index];
+            rest.len -= file.len;
+            if start == rest.start {
+                rest.start += file.len;
+            }
+        }
+    }
+
+    pub fn checksum(&self) -> usize {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, id)| id.map(|id| pos * id))
+            .sum()
+    }
+
+    /// The puzzle's `00...111...2` picture, one digit per file block and `.`
+    /// per free block. File ids past 9 have no single digit, so bigger disks
+    /// aren't drawn.
+    pub fn render(&self) -> Option<String> {
+        if self.files.len() > 10 {
+            return None;
+        }
+        Some(
+            self.blocks
+                .iter()
+                .map(|id| id.map_or('.', |id| char::from_digit(id as u32, 10).unwrap()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_matches_disk_map() {
+        let disk: Vec<usize> = include_str!("../../day9/sample.txt")
+            .trim()
+            .bytes()
+            .map(|b| (b - b'0') as usize)
+            .collect();
+        for (strategy, checksum) in [(Strategy::Blocks, 1928), (Strategy::FirstFit, 2858)] {
+            let mut map = DiskMap::parse(&disk);
+            map.compact(strategy);
+            assert_eq!(map.checksum(), checksum);
+        }
+        assert_eq!(part1(&disk), 1928);
+        assert_eq!(part2(&disk), 2858);
+    }
+}
@@ -1,26 +1,36 @@
 use std::collections::{HashSet, VecDeque};
-use std::io::{self};
-
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = read_from_file("day10/input.txt")?;
-
-    // Convert data (a single string) to a Vec of i64s, there is no spacing it's a single long string
-    let mut vals: Vec<Vec<u8>> = Vec::new();
-    for line in data.lines() {
-        let mut row: Vec<u8> = Vec::new();
-        for c in line.chars() {
-            match c.to_digit(10) {
-                Some(d) => row.push(d as u8),
-                None => continue,
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+use aoc_core::span;
+
+/// The topographic map of heights.
+pub const INPUT: Shape = Shape::Grid("0123456789");
+
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let vals = {
+        span!("parse");
+        let data = input::read("day10/input.txt")?;
+
+        // Convert data (a single string) to a Vec of i64s, there is no spacing it's a single long string
+        let mut vals: Vec<Vec<u8>> = Vec::new();
+        for line in data.lines() {
+            let mut row: Vec<u8> = Vec::new();
+            for c in line.chars() {
+                match c.to_digit(10) {
+                    Some(d) => row.push(d as u8),
+                    None => continue,
+                }
             }
+            vals.push(row);
         }
-        vals.push(row);
-    }
+        vals
+    };
 
     let total_score = calculate_total_score(vals.clone());
     let total_rating = calculate_total_rating(vals.clone());
-    println!("Day 10 Part 1: {}", total_score);
-    println!("Day 10 Part 2: {}", total_rating);
+    answer(1, total_score);
+    answer(2, total_rating);
 
     Ok(())
 }
@@ -40,6 +50,7 @@ fn in_bounds(x: isize, y: isize, height: usize, width: usize) -> bool {
 
 /// Perform a breadth-first search (BFS) to find all 9-height positions reachable from a given trailhead.
 fn bfs_find_trail_score(map: &Vec<Vec<u8>>, start_x: usize, start_y: usize) -> usize {
+    span!("bfs");
     let height = map.len();
     let width = map[0].len();
     let mut visited: HashSet<(usize, usize)> = HashSet::new();
@@ -85,6 +96,7 @@ fn dfs_count_trails(
     current_height: u8,
     visited: &mut HashSet<(usize, usize)>,
 ) -> usize {
+    span!("dfs");
     if visited.contains(&(x, y)) || map[x][y] != current_height {
         return 0;
     }
@@ -113,6 +125,7 @@ fn dfs_count_trails(
 
 /// Calculate the total score for all trailheads in the height map.
 fn calculate_total_score(map: Vec<Vec<u8>>) -> usize {
+    span!("score");
     let mut total_score = 0;
     let height = map.len();
     let width = map[0].len();
@@ -132,6 +145,7 @@ fn calculate_total_score(map: Vec<Vec<u8>>) -> usize {
 
 /// Calculate the total rating for all trailheads in the height map.
 fn calculate_total_rating(map: Vec<Vec<u8>>) -> usize {
+    span!("rating");
     let mut total_rating = 0;
     let height = map.len();
     let width = map[0].len();
@@ -149,15 +163,3 @@ fn calculate_total_rating(map: Vec<Vec<u8>>) -> usize {
 
     total_rating
 }
-
-fn read_from_file(file_path: &str) -> Result<String, io::Error> {
-    // Read the data from the specified file path
-    let data = match std::fs::read_to_string(file_path) {
-        Ok(d) => d,
-        Err(e) => {
-            println!("Error reading file");
-            return Err(e);
-        }
-    };
-    Ok(data)
-}
@@ -1,39 +1,80 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::{HashMap, HashSet};
+use std::io;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = read_from_file("day11/input.txt")?;
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+use aoc_core::span;
+
+/// The stones, on one line.
+pub const INPUT: Shape = Shape::Line("<ints>");
 
-    let mut stones = HashMap::new();
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day11_options")]
+pub struct Options {
+    /// Day 11: blink counts to report, e.g. `--blinks 25,75,500` [default: 25,75]
+    #[clap(long, value_delimiter = ',')]
+    pub blinks: Vec<usize>,
+
+    /// Day 11: report the set of stone values reachable from the input and how
+    /// many distinct values are on the line after each blink
+    #[clap(long, action)]
+    pub stone_analysis: bool,
+}
 
-    for d in data {
-        *stones.entry(d).or_default() += 1;
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day11;
+    let data = read_from_file("day11/input.txt")?;
+
+    let mut stones: HashMap<u64, u128> = HashMap::new();
+    for d in data.iter() {
+        *stones.entry(*d).or_default() += 1;
     }
 
-    for i in 0..75 {
-        if i == 25 {
-            println!("Day 11 Part 1: {}", stones.values().sum::<u64>());
+    // Without explicit counts this is the puzzle: part 1 after 25, part 2 after 75.
+    let (targets, labelled) = if options.blinks.is_empty() {
+        (vec![25, 75], true)
+    } else {
+        let mut targets = options.blinks.clone();
+        targets.sort_unstable();
+        targets.dedup();
+        (targets, false)
+    };
+
+    let mut history = vec![stones.len()];
+    let mut blinks = 0;
+    for (part, &target) in targets.iter().enumerate() {
+        while blinks < target {
+            stones = blink(stones)?;
+            blinks += 1;
+            history.push(stones.len());
+        }
+
+        let total = total_stones(&stones).ok_or_else(|| overflow(blinks))?;
+        if labelled {
+            answer(part as u32 + 1, total);
+        } else {
+            println!("Day 11 after {} blinks: {}", target, total);
         }
-        stones = blink(stones);
     }
 
-    println!("Day 11 Part 2: {}", stones.values().sum::<u64>());
+    if options.stone_analysis {
+        let current = stones.keys().copied().collect();
+        print_analysis(&data, current, history)?;
+    }
 
     Ok(())
 }
 
 fn read_from_file(filename: &str) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
+    span!("parse");
+    let content = input::read(filename)?;
 
     // Input data is a single list with a space separated list of numbers
     // 773 79858 0 71 213357 2937 1 3998391
     // Want to convert into a Vec of u64s
 
     let mut data: Vec<u64> = Vec::new();
-    for line in reader.lines() {
-        let line = line?;
+    for line in content.lines() {
         for num in line.split_whitespace() {
             data.push(num.parse()?);
         }
@@ -42,23 +83,174 @@ fn read_from_file(filename: &str) -> Result<Vec<u64>, Box<dyn std::error::Error>
     Ok(data)
 }
 
-fn blink(stones: HashMap<u64, u64>) -> HashMap<u64, u64> {
-    let mut new_stones = HashMap::with_capacity(stones.len());
+fn overflow(blinks: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "stone count no longer fits in a u128 after {} blinks",
+            blinks
+        ),
+    )
+}
+
+fn total_stones(stones: &HashMap<u64, u128>) -> Option<u128> {
+    stones
+        .values()
+        .try_fold(0u128, |acc, &v| acc.checked_add(v))
+}
+
+/// Splits a number with an even count of digits into its left and right
+/// halves, e.g. 253000 -> (253, 0).
+fn split_digits(n: u64) -> Option<(u64, u64)> {
+    let digits = n.checked_ilog10()? + 1;
+    if digits % 2 == 1 {
+        return None;
+    }
+    let half = 10u64.pow(digits / 2);
+    Some((n / half, n % half))
+}
+
+/// What a single stone turns into after one blink.
+fn next_stones(k: u64) -> Result<(u64, Option<u64>), io::Error> {
+    if k == 0 {
+        return Ok((1, None));
+    }
+    if let Some((l, r)) = split_digits(k) {
+        return Ok((l, Some(r)));
+    }
+    match k.checked_mul(2024) {
+        Some(v) => Ok((v, None)),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("stone {} * 2024 does not fit in a u64", k),
+        )),
+    }
+}
+
+fn blink(stones: HashMap<u64, u128>) -> Result<HashMap<u64, u128>, io::Error> {
+    span!("blink");
+    let mut new_stones: HashMap<u64, u128> = HashMap::with_capacity(stones.len());
     for (k, v) in stones {
-        let num = format!("{}", k);
-        match k {
-            0 => *new_stones.entry(1).or_default() += v,
-            _ => {
-                if num.len() % 2 > 0 {
-                    *new_stones.entry(2024 * k).or_default() += v;
-                } else {
-                    let l: u64 = num[..num.len() / 2].parse().unwrap();
-                    let r: u64 = num[num.len() / 2..].parse().unwrap();
-                    *new_stones.entry(l).or_default() += v;
-                    *new_stones.entry(r).or_default() += v;
-                }
+        let (l, r) = next_stones(k)?;
+        for stone in std::iter::once(l).chain(r) {
+            let count = new_stones.entry(stone).or_default();
+            *count = count.checked_add(v).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "stone count overflowed a u128")
+            })?;
+        }
+    }
+    Ok(new_stones)
+}
+
+/// Every stone value that can ever appear when starting from `initial`.
+fn reachable_values(initial: &[u64]) -> Result<HashSet<u64>, io::Error> {
+    let mut seen: HashSet<u64> = initial.iter().copied().collect();
+    let mut stack: Vec<u64> = seen.iter().copied().collect();
+
+    while let Some(k) = stack.pop() {
+        let (l, r) = next_stones(k)?;
+        for stone in std::iter::once(l).chain(r) {
+            if seen.insert(stone) {
+                stack.push(stone);
             }
-        };
+        }
+    }
+
+    Ok(seen)
+}
+
+/// `history[i]` is the number of distinct stone values after `i` blinks, and
+/// `current` the values on the line after the last of them. Carries on
+/// blinking the values alone until they settle, at most once per reachable
+/// value.
+fn print_analysis(
+    initial: &[u64],
+    current: HashSet<u64>,
+    mut history: Vec<usize>,
+) -> Result<(), io::Error> {
+    span!("analysis");
+    let closure = reachable_values(initial)?;
+    println!(
+        "Day 11 analysis: {} distinct stone values are reachable from the input (largest {})",
+        closure.len(),
+        closure.iter().max().copied().unwrap_or(0)
+    );
+
+    blink_until_settled(current, &mut history, closure.len())?;
+    for (i, distinct) in history.iter().enumerate() {
+        println!("  blink {:>4}: {:>6} distinct values", i, distinct);
+    }
+    println!("Day 11 analysis: {}", settle_report(&history));
+
+    Ok(())
+}
+
+/// Blinks the distinct `values` until a blink leaves them unchanged, or
+/// until `history` covers `limit` blinks, adding each blink's count.
+fn blink_until_settled(
+    mut values: HashSet<u64>,
+    history: &mut Vec<usize>,
+    limit: usize,
+) -> Result<(), io::Error> {
+    while history.len() <= limit {
+        let mut next = HashSet::with_capacity(values.len());
+        for &k in &values {
+            let (l, r) = next_stones(k)?;
+            next.extend(std::iter::once(l).chain(r));
+        }
+        history.push(next.len());
+        if next == values {
+            break;
+        }
+        values = next;
+    }
+    Ok(())
+}
+
+/// When the distinct count stopped changing. Once the values stop changing
+/// the line only grows in number, never in variety, so every later blink is
+/// the same map with bigger counts.
+fn settle_report(history: &[usize]) -> String {
+    let blinks = history.len().saturating_sub(1);
+    match history {
+        [.., before, last] if before == last => {
+            let from = history.iter().rposition(|d| d != last).map_or(0, |i| i + 1);
+            format!(
+                "distinct values settle at {} from blink {} onwards",
+                last, from
+            )
+        }
+        _ => format!("distinct values not settled after {} blinks", blinks),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settle_reports() {
+        assert_eq!(
+            settle_report(&[2, 5, 9, 9, 9]),
+            "distinct values settle at 9 from blink 2 onwards"
+        );
+        assert_eq!(
+            settle_report(&[3747, 3757, 3767]),
+            "distinct values not settled after 2 blinks"
+        );
+        assert_eq!(
+            settle_report(&[2]),
+            "distinct values not settled after 0 blinks"
+        );
+    }
+
+    #[test]
+    fn sample_settles_within_its_closure() {
+        let initial = [125, 17];
+        let closure = reachable_values(&initial).unwrap();
+        let mut history = vec![initial.len()];
+        blink_until_settled(initial.into(), &mut history, closure.len()).unwrap();
+        assert!(history.len() <= closure.len() + 1);
+        assert!(settle_report(&history).starts_with("distinct values settle at "));
     }
-    new_stones
 }
@@ -1,143 +1,312 @@
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    // read in the data from day12/sample.txt and parse into a Vec<Vec<char>>
-    let data = read_from_file("day12/input.txt")?;
-
-    // data is a map of chars, find the number of groups where the char is the same
-    // the char can be any letter. And a region can be any number of the same letter
-    // the region can move horizontally or vertically. Find all the regions using
-    // a depth first search in a Vec<HashSet<(usize, usize)>> where the usize is the
-    // row and column of the char in the data Vec<Vec<char>>.
-
-    let mut seen: Vec<Vec<bool>> = vec![vec![false; data[0].len()]; data.len()];
-
-    let mut maps: Vec<Vec<(usize, usize)>> = Vec::new();
-
-    for i in 0..data.len() {
-        for j in 0..data[i].len() {
-            if !seen[i][j] {
-                let new_map = dfs(&data, &mut seen, i, j);
-                for (x, y) in new_map.clone() {
-                    seen[x][y] = true;
-                }
-                maps.push(new_map);
-            }
-        }
-    }
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
 
-    let mut sum: usize = 0;
-    for map in maps.iter() {
-        sum += perimeter(map.clone()) * map.len();
-    }
+use aoc_core::graph::UnionFind;
+use aoc_core::grid::Grid;
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The garden plots, one letter per plant.
+pub const INPUT: Shape = Shape::Grid("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
 
-    println!("Day 12 Part 1: {}", sum);
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day12_options")]
+pub struct Options {
+    /// Day 12: write every region's fence to this SVG file, with its area,
+    /// perimeter and side count
+    #[clap(long)]
+    pub svg: Option<PathBuf>,
+}
 
-    let mut sum: usize = 0;
-    for map in maps.iter() {
-        let v: Vec<(i32, i32)> = map.iter().map(|(x, y)| (*x as i32, *y as i32)).collect();
-        sum += count_region_sides(v.clone()) * map.len();
+/// Garden groups. Using `day12/sample.txt` gives 140 and 80, `sample2.txt`
+/// gives 772 and 436 and `sample3.txt` gives 1930 and 1206.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day12;
+    let garden = Grid::parse(&input::read("day12/input.txt")?)?;
+
+    let labels = label_regions(&garden);
+    let regions = measure_regions(&garden, &labels);
+
+    let price: usize = regions.iter().map(|r| r.area * r.perimeter).sum();
+    answer(1, price);
+
+    let price: usize = regions.iter().map(|r| r.area * r.sides).sum();
+    answer(2, price);
+
+    if let Some(path) = &options.svg {
+        fs::write(path, fence_svg(&labels, &regions))?;
+        println!("Day 12 fences written to {}", path.display());
     }
 
-    println!("Day 12 Part 2: {}", sum);
     Ok(())
 }
 
-fn dfs(
-    data: &Vec<Vec<char>>,
-    seen: &mut Vec<Vec<bool>>,
-    i: usize,
-    j: usize,
-) -> Vec<(usize, usize)> {
-    let mut stack = vec![(i, j)];
-    let mut new_map = Vec::new();
-
-    while let Some((x, y)) = stack.pop() {
-        if x >= data.len() || y >= data[x].len() || seen[x][y] {
-            continue;
-        }
-
-        seen[x][y] = true;
-        new_map.push((x, y));
+/// Labels every plot with its region, numbered from 0 in reading order of
+/// each region's first plot. Each plot is joined with the matching plots to
+/// its right and below.
+fn label_regions(garden: &Grid<char>) -> Grid<usize> {
+    let width = garden.width();
+    let mut sets = UnionFind::new(width * garden.height());
 
-        if x > 0 && data[x - 1][y] == data[x][y] {
-            stack.push((x - 1, y));
+    for (x, y) in garden.positions() {
+        let plant = garden[(x, y)];
+        if x + 1 < width && garden[(x + 1, y)] == plant {
+            sets.union(y * width + x, y * width + x + 1);
         }
-        if y > 0 && data[x][y - 1] == data[x][y] {
-            stack.push((x, y - 1));
-        }
-        if x < data.len() - 1 && data[x + 1][y] == data[x][y] {
-            stack.push((x + 1, y));
-        }
-        if y < data[x].len() - 1 && data[x][y + 1] == data[x][y] {
-            stack.push((x, y + 1));
+        if y + 1 < garden.height() && garden[(x, y + 1)] == plant {
+            sets.union(y * width + x, (y + 1) * width + x);
         }
     }
 
-    new_map
+    let mut labels = Grid::new(width, garden.height(), 0);
+    let mut numbering = HashMap::new();
+    for (x, y) in garden.positions() {
+        let root = sets.find(y * width + x);
+        let next = numbering.len();
+        labels[(x, y)] = *numbering.entry(root).or_insert(next);
+    }
+    labels
 }
 
-fn read_from_file(filename: &str) -> Result<Vec<Vec<char>>, Box<dyn std::error::Error>> {
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
+struct Region {
+    plant: char,
+    area: usize,
+    perimeter: usize,
+    sides: usize,
+    first: (usize, usize),
+}
 
-    let mut data: Vec<Vec<char>> = Vec::new();
-    for line in reader.lines() {
-        let line = line?;
-        let mut row: Vec<char> = Vec::new();
-        for c in line.chars() {
-            row.push(c);
+const DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const DIAGONALS: [(i64, i64); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+/// A polygon has as many sides as corners, so sides are counted by looking at
+/// each plot's four corners. A corner is outside when neither neighbour
+/// towards it is in the region, and inside when both are but the diagonal
+/// plot between them isn't.
+fn measure_regions(garden: &Grid<char>, labels: &Grid<usize>) -> Vec<Region> {
+    let mut regions: Vec<Region> = Vec::new();
+
+    for (x, y) in labels.positions() {
+        let label = labels[(x, y)];
+        if label == regions.len() {
+            regions.push(Region {
+                plant: garden[(x, y)],
+                area: 0,
+                perimeter: 0,
+                sides: 0,
+                first: (x, y),
+            });
         }
-        data.push(row);
+
+        let same = |dx: i64, dy: i64| labels.get(x as i64 + dx, y as i64 + dy) == Some(&label);
+        let region = &mut regions[label];
+        region.area += 1;
+        region.perimeter += DIRECTIONS.iter().filter(|&&(dx, dy)| !same(dx, dy)).count();
+        region.sides += DIAGONALS
+            .iter()
+            .filter(|&&(dx, dy)| {
+                let (across, down) = (same(dx, 0), same(0, dy));
+                (!across && !down) || (across && down && !same(dx, dy))
+            })
+            .count();
     }
 
-    Ok(data)
+    regions
 }
-fn perimeter(points: Vec<(usize, usize)>) -> usize {
-    let mut perimeter = 0;
-    let point_set: HashSet<(usize, usize)> = points.into_iter().collect();
-
-    // Directions representing top, bottom, left, and right neighbors
-    let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-
-    for &(x, y) in &point_set {
-        let mut exposed_sides = 4; // Each cell has 4 sides
-        for &(dx, dy) in &directions {
-            let neighbor = (x.wrapping_add(dx as usize), y.wrapping_add(dy as usize));
-            if point_set.contains(&neighbor) {
-                exposed_sides -= 1; // Neighboring point reduces the number of exposed sides
+
+/// Pixels per plot in the SVG.
+const SCALE: usize = 16;
+
+/// Draws each region as one path made of its fence loops (holes included),
+/// with the counts in a hover title and, for regions big enough to hold it,
+/// as a label on the first plot.
+fn fence_svg(labels: &Grid<usize>, regions: &[Region]) -> String {
+    let mut svg = String::new();
+    let (width, height) = (labels.width() * SCALE, labels.height() * SCALE);
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+
+    for (label, loops) in fence_loops(labels).iter().enumerate() {
+        let region = &regions[label];
+        let hue = (region.plant as u32 * 47) % 360;
+
+        let mut d = String::new();
+        for fence in loops {
+            for (i, &(x, y)) in fence.iter().enumerate() {
+                let cmd = if i == 0 { 'M' } else { 'L' };
+                write!(d, "{}{} {} ", cmd, x * SCALE, y * SCALE).unwrap();
             }
+            d.push('Z');
+        }
+
+        let summary = format!(
+            "{}: area {}, perimeter {}, sides {}",
+            region.plant, region.area, region.perimeter, region.sides
+        );
+        writeln!(
+            svg,
+            r#"  <path d="{d}" fill="hsl({hue},60%,75%)" fill-rule="evenodd" stroke="black" stroke-width="1"><title>{summary}</title></path>"#
+        )
+        .unwrap();
+
+        if region.area >= 9 {
+            let (x, y) = region.first;
+            writeln!(
+                svg,
+                r#"  <text x="{}" y="{}" font-size="{}" font-family="monospace">{} {}/{}/{}</text>"#,
+                x * SCALE + 2,
+                y * SCALE + SCALE * 3 / 4,
+                SCALE / 2,
+                region.plant,
+                region.area,
+                region.perimeter,
+                region.sides
+            )
+            .unwrap();
         }
-        perimeter += exposed_sides;
     }
 
-    perimeter
+    svg.push_str("</svg>\n");
+    svg
 }
 
-fn count_region_sides(region: Vec<(i32, i32)>) -> usize {
-    let mut side_count = 0;
-    for dir in DIR {
-        let mut sides = HashSet::new();
-        for pos in region.iter() {
-            let tmp = (pos.0 + dir.0, pos.1 + dir.1);
-            if !region.contains(&tmp) {
-                sides.insert(tmp);
+type Vertex = (usize, usize);
+
+/// The closed fence loops of each region as lists of corner vertices. Every
+/// fence piece is directed so its region is on the right, which makes each
+/// vertex have as many pieces leaving it as arriving, so following pieces from
+/// any start always comes back round. Straight runs are merged into one edge.
+fn fence_loops(labels: &Grid<usize>) -> Vec<Vec<Vec<Vertex>>> {
+    let regions = labels
+        .positions()
+        .map(|p| labels[p])
+        .max()
+        .map_or(0, |m| m + 1);
+    let mut pieces: Vec<HashMap<Vertex, Vec<Vertex>>> = vec![HashMap::new(); regions];
+
+    for (x, y) in labels.positions() {
+        let label = labels[(x, y)];
+        let differs = |dx: i64, dy: i64| labels.get(x as i64 + dx, y as i64 + dy) != Some(&label);
+        let edges = [
+            ((0, -1), (x, y), (x + 1, y)),
+            ((1, 0), (x + 1, y), (x + 1, y + 1)),
+            ((0, 1), (x + 1, y + 1), (x, y + 1)),
+            ((-1, 0), (x, y + 1), (x, y)),
+        ];
+        for ((dx, dy), from, to) in edges {
+            if differs(dx, dy) {
+                pieces[label].entry(from).or_default().push(to);
             }
         }
-        let mut remove: HashSet<(i32, i32)> = HashSet::default();
-        for side in &sides {
-            let mut tmp = (side.0 + dir.1, side.1 + dir.0);
-            while sides.contains(&tmp) {
-                remove.insert(tmp);
-                tmp = (tmp.0 + dir.1, tmp.1 + dir.0);
+    }
+
+    pieces
+        .into_iter()
+        .map(|mut outgoing| {
+            let mut loops = Vec::new();
+            let mut starts: Vec<Vertex> = outgoing.keys().copied().collect();
+            starts.sort();
+            for start in starts {
+                while outgoing.get(&start).is_some_and(|v| !v.is_empty()) {
+                    let mut fence = vec![start];
+                    let mut at = start;
+                    loop {
+                        let next = outgoing.get_mut(&at).and_then(|v| v.pop()).unwrap();
+                        if next == start {
+                            break;
+                        }
+                        fence.push(next);
+                        at = next;
+                    }
+                    loops.push(merge_straight_runs(fence));
+                }
             }
+            loops
+        })
+        .collect()
+}
+
+fn merge_straight_runs(fence: Vec<Vertex>) -> Vec<Vertex> {
+    let n = fence.len();
+    (0..n)
+        .filter(|&i| {
+            let (prev, here, next) = (fence[(i + n - 1) % n], fence[i], fence[(i + 1) % n]);
+            let straight_x = prev.0 == here.0 && here.0 == next.0;
+            let straight_y = prev.1 == here.1 && here.1 == next.1;
+            !(straight_x || straight_y)
+        })
+        .map(|i| fence[i])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    // The corner count replaced a scan along each side that was hard to follow;
+    // check the two agree on every region of the samples.
+    #[test]
+    fn corners_match_fence_scan() {
+        for sample in [
+            include_str!("../../day12/sample.txt"),
+            include_str!("../../day12/sample2.txt"),
+            include_str!("../../day12/sample3.txt"),
+        ] {
+            let garden = Grid::parse(sample).unwrap();
+            let labels = label_regions(&garden);
+            check_sides(&labels, &measure_regions(&garden, &labels));
         }
-        side_count += sides.len() - remove.len();
     }
 
-    side_count as usize
-}
+    /// Cross-checks the corner count against walking the fences.
+    fn check_sides(labels: &Grid<usize>, regions: &[Region]) {
+        let mut plots = vec![HashSet::new(); regions.len()];
+        for (x, y) in labels.positions() {
+            plots[labels[(x, y)]].insert((x as i32, y as i32));
+        }
+        for (region, plots) in regions.iter().zip(plots.iter()) {
+            assert_eq!(
+                region.sides,
+                count_region_sides(plots),
+                "side count disagrees for region {} at {:?}",
+                region.plant,
+                region.first
+            );
+        }
+    }
+
+    /// Collects the fence pieces facing each direction, then drops every piece
+    /// that continues a fence already counted, leaving one piece per side.
+    fn count_region_sides(region: &HashSet<(i32, i32)>) -> usize {
+        let mut side_count = 0;
+        for dir in DIR {
+            let mut sides = HashSet::new();
+            for pos in region.iter() {
+                let tmp = (pos.0 + dir.0, pos.1 + dir.1);
+                if !region.contains(&tmp) {
+                    sides.insert(tmp);
+                }
+            }
+            let mut remove: HashSet<(i32, i32)> = HashSet::default();
+            for side in &sides {
+                let mut tmp = (side.0 + dir.1, side.1 + dir.0);
+                while sides.contains(&tmp) {
+                    remove.insert(tmp);
+                    tmp = (tmp.0 + dir.1, tmp.1 + dir.0);
+                }
+            }
+            side_count += sides.len() - remove.len();
+        }
 
-const DIR: [(i32, i32); 4] = [(1, 0), (0, 1), (0, -1), (-1, 0)];
+        side_count
+    }
+
+    const DIR: [(i32, i32); 4] = [(1, 0), (0, 1), (0, -1), (-1, 0)];
+}
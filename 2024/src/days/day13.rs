@@ -1,202 +1,253 @@
+use aoc_core::input::{self, Shape};
+use aoc_core::linalg::{self, AffineSolution, Rational};
+use aoc_core::runner::answer;
 use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::open("day13/input.txt")?;
-    let reader = BufReader::new(file);
-
-    let mut total_tokens = 0;
-    let mut idx = 0;
-    let mut matrix = Matrix2x2 {
-        a: 0,
-        b: 0,
-        c: 0,
-        d: 0,
-    };
-    let mut b: Vector2 = Vector2 { x: 0, y: 0 };
-    for line in reader.lines() {
-        let line = line?;
-
-        if idx % 4 == 0 || idx % 4 == 1 {
-            let re = Regex::new(r"X\+(\d+), Y\+(\d+)").unwrap();
-
-            if let Some(captures) = re.captures(line.as_str()) {
-                let x_value = captures.get(1).unwrap().as_str().parse::<i64>().unwrap();
-                let y_value = captures.get(2).unwrap().as_str().parse::<i64>().unwrap();
-
-                if idx % 4 == 0 {
-                    matrix.a = x_value as i64;
-                    matrix.c = y_value as i64;
-                } else {
-                    matrix.b = x_value as i64;
-                    matrix.d = y_value as i64;
-                }
-            } else {
-                println!("No match found");
-            }
-        } else if idx % 4 == 2 {
-            let re = Regex::new(r"Prize: X=(\d+), Y=(\d+)").unwrap();
-
-            if let Some(captures) = re.captures(line.as_str()) {
-                let x_value = captures.get(1).unwrap().as_str().parse::<i64>().unwrap();
-                let y_value = captures.get(2).unwrap().as_str().parse::<i64>().unwrap();
-                b.x = x_value as i64;
-                b.y = y_value as i64;
-            } else {
-                println!("No match found: {}", line);
-            }
-        } else {
-            match solve_linear_system(matrix, b) {
-                Some(solution) => {
-                    if validate(&matrix, &b, &solution) {
-                        let t = (3 * (solution.x as i64) + (solution.y as i64)) as i64;
-                        total_tokens += t;
-                    }
-                }
-                None => {}
-            }
-        }
+use std::io;
+
+/// A block of button and prize lines per claw machine.
+pub const INPUT: Shape = Shape::Repeated(&Shape::Rows(&[
+    "Button A: X+<int>, Y+<int>",
+    "Button B: X+<int>, Y+<int>",
+    "Prize: X=<int>, Y=<int>",
+]));
+
+const PART2_OFFSET: i128 = 10000000000000;
+
+#[derive(clap::Args, Debug, Clone)]
+#[group(id = "day13_options")]
+pub struct Options {
+    /// Day 13: token cost of each button, in the order they appear in a machine
+    #[clap(long, value_delimiter = ',', default_values_t = [3, 1])]
+    pub button_costs: Vec<i128>,
+}
 
-        idx += 1;
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            button_costs: vec![3, 1],
+        }
     }
-    match solve_linear_system(matrix, b) {
-        Some(solution) => {
-            if validate(&matrix, &b, &solution) {
-                let t = (3 * (solution.x as i64) + (solution.y as i64)) as i64;
-                total_tokens += t;
-            }
+}
+
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day13;
+    let data = input::read("day13/input.txt")?;
+    let machines = parse_machines(&data)?;
+
+    for machine in machines.iter() {
+        if machine.buttons.len() > options.button_costs.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "a machine has {} buttons but only {} costs were given",
+                    machine.buttons.len(),
+                    options.button_costs.len()
+                ),
+            )
+            .into());
         }
-        None => {}
     }
 
-    println!("Day 13 Part 1: {}", total_tokens);
+    let total = |offset: i128| -> io::Result<i128> {
+        let mut sum = 0;
+        for machine in machines.iter() {
+            sum += cheapest(machine, &options.button_costs, offset)?.unwrap_or(0);
+        }
+        Ok(sum)
+    };
+
+    answer(1, total(0)?);
+    answer(2, total(PART2_OFFSET)?);
 
-    run2()
+    Ok(())
 }
 
-fn run2() -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::open("day13/input.txt")?;
-    let reader = BufReader::new(file);
-
-    let mut total_tokens = 0;
-    let mut idx = 0;
-    let mut matrix = Matrix2x2 {
-        a: 0,
-        b: 0,
-        c: 0,
-        d: 0,
-    };
-    let mut b: Vector2 = Vector2 { x: 0, y: 0 };
-    for line in reader.lines() {
-        let line = line?;
-
-        if idx % 4 == 0 || idx % 4 == 1 {
-            let re = Regex::new(r"X\+(\d+), Y\+(\d+)").unwrap();
-
-            if let Some(captures) = re.captures(line.as_str()) {
-                let x_value = captures.get(1).unwrap().as_str().parse::<i64>().unwrap();
-                let y_value = captures.get(2).unwrap().as_str().parse::<i64>().unwrap();
-
-                if idx % 4 == 0 {
-                    matrix.a = x_value as i64;
-                    matrix.c = y_value as i64;
-                } else {
-                    matrix.b = x_value as i64;
-                    matrix.d = y_value as i64;
-                }
-            } else {
-                println!("No match found");
-            }
-        } else if idx % 4 == 2 {
-            let re = Regex::new(r"Prize: X=(\d+), Y=(\d+)").unwrap();
-
-            if let Some(captures) = re.captures(line.as_str()) {
-                let x_value = captures.get(1).unwrap().as_str().parse::<i64>().unwrap();
-                let y_value = captures.get(2).unwrap().as_str().parse::<i64>().unwrap();
-                b.x = x_value + 10000000000000;
-                b.y = y_value + 10000000000000;
+#[derive(Debug, Clone)]
+struct Machine {
+    /// How far each button moves the claw along (X, Y).
+    buttons: Vec<(i128, i128)>,
+    prize: (i128, i128),
+}
+
+/// Machines are separated by blank lines and may have any number of buttons.
+fn parse_machines(data: &str) -> Result<Vec<Machine>, Box<dyn std::error::Error>> {
+    let button_re = Regex::new(r"^Button \w+: X\+(\d+), Y\+(\d+)$")?;
+    let prize_re = Regex::new(r"^Prize: X=(\d+), Y=(\d+)$")?;
+
+    let mut machines = Vec::new();
+    for block in data.split("\n\n") {
+        let mut buttons = Vec::new();
+        let mut prize = None;
+
+        for line in block.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(captures) = button_re.captures(line) {
+                buttons.push((captures[1].parse()?, captures[2].parse()?));
+            } else if let Some(captures) = prize_re.captures(line) {
+                prize = Some((captures[1].parse()?, captures[2].parse()?));
             } else {
-                println!("No match found: {}", line);
-            }
-        } else {
-            match solve_linear_system(matrix, b) {
-                Some(solution) => {
-                    if validate(&matrix, &b, &solution) {
-                        let t = (3 * (solution.x as i64) + (solution.y as i64)) as i64;
-                        total_tokens += t;
-                    }
-                }
-                None => {}
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unrecognised line: {}", line),
+                )
+                .into());
             }
         }
 
-        idx += 1;
-    }
-    match solve_linear_system(matrix, b) {
-        Some(solution) => {
-            if validate(&matrix, &b, &solution) {
-                let t = (3 * (solution.x as i64) + (solution.y as i64)) as i64;
-                total_tokens += t;
+        match prize {
+            Some(prize) => machines.push(Machine { buttons, prize }),
+            None if buttons.is_empty() => continue,
+            None => {
+                return Err(
+                    io::Error::new(io::ErrorKind::InvalidData, "machine without a prize").into(),
+                )
             }
         }
-        None => {}
     }
 
-    println!("Day 13 Part 2: {}", total_tokens);
-
-    Ok(())
+    Ok(machines)
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Matrix2x2 {
-    a: i64,
-    b: i64,
-    c: i64,
-    d: i64,
-}
+/// The most combinations of presses [`min_cost`] will try for the free
+/// variables it has to enumerate.
+const MAX_SEARCH: i128 = 10_000_000;
+
+/// The fewest tokens needed to win the prize (moved by `offset` on both axes),
+/// or `None` if no combination of presses reaches it. Machines with more than
+/// one press count left free by the equations can only be searched while the
+/// presses are small, so part 2's offset is an error for them.
+fn cheapest(machine: &Machine, costs: &[i128], offset: i128) -> io::Result<Option<i128>> {
+    let a = vec![
+        machine.buttons.iter().map(|b| b.0).collect::<Vec<_>>(),
+        machine.buttons.iter().map(|b| b.1).collect::<Vec<_>>(),
+    ];
+    let target = [machine.prize.0 + offset, machine.prize.1 + offset];
+
+    // No button can be pressed more often than it takes to overshoot the prize
+    // on an axis it moves along. A button that moves nowhere is never worth it.
+    let bounds: Vec<i128> = (0..machine.buttons.len())
+        .map(|j| {
+            (0..a.len())
+                .filter(|&i| a[i][j] > 0)
+                .map(|i| target[i] / a[i][j])
+                .min()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let Some(solution) = linalg::solve(&a, &target) else {
+        return Ok(None);
+    };
+
+    // Every free variable but the loosest one is enumerated.
+    let mut ranges: Vec<i128> = solution.free.iter().map(|&j| bounds[j] + 1).collect();
+    ranges.sort_unstable();
+    ranges.pop();
+    let search = ranges
+        .iter()
+        .try_fold(1i128, |acc, &r| acc.checked_mul(r))
+        .filter(|&n| n <= MAX_SEARCH);
+    if search.is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "a machine with {} buttons leaves {} presses free, too many to search for a prize this far away",
+                machine.buttons.len(),
+                solution.free.len()
+            ),
+        ));
+    }
 
-#[derive(Debug, Clone, Copy)]
-struct Vector2 {
-    x: i64,
-    y: i64,
+    Ok(min_cost(
+        &solution,
+        &costs[..machine.buttons.len()],
+        &bounds,
+    ))
 }
 
-impl Matrix2x2 {
-    /// Computes the determinant of the 2x2 matrix
-    fn determinant(&self) -> i64 {
-        self.a * self.d - self.b * self.c
+/// Minimises `Σ costs[j] * x[j]` over non-negative integer points of the
+/// solution set with `x[j] <= bounds[j]`. One free variable is solved in closed
+/// form; any more are enumerated, so the caller keeps their bounds small.
+fn min_cost(solution: &AffineSolution, costs: &[i128], bounds: &[i128]) -> Option<i128> {
+    match solution.free.len() {
+        0 => {
+            let mut total = 0;
+            for (&x, &cost) in solution.particular.iter().zip(costs.iter()) {
+                let x = x.to_integer()?;
+                if x < 0 {
+                    return None;
+                }
+                total += x * cost;
+            }
+            Some(total)
+        }
+        1 => min_cost_on_line(solution, costs, bounds),
+        _ => {
+            // With more buttons than axes, enumerate the free variable with
+            // the tightest bound until a single one is left.
+            let k = (0..solution.free.len())
+                .min_by_key(|&k| bounds[solution.free[k]])
+                .unwrap();
+            (0..=bounds[solution.free[k]])
+                .filter_map(|t| min_cost(&solution.fix(k, Rational::from(t)), costs, bounds))
+                .min()
+        }
     }
 }
 
-fn solve_linear_system(a: Matrix2x2, b: Vector2) -> Option<Vector2> {
-    let det = a.determinant();
-    if det == 0 {
-        // No unique solution exists if the determinant is zero
+/// Solutions along a line `x = p + t * d`. The integer values of `t` that give
+/// an all-integer `x` form an arithmetic progression found with extended
+/// Euclid, non-negativity cuts it to an interval, and since the cost is linear
+/// in `t` the optimum sits at one end of it.
+fn min_cost_on_line(solution: &AffineSolution, costs: &[i128], bounds: &[i128]) -> Option<i128> {
+    let free = solution.free[0];
+    let (p, d) = (&solution.particular, &solution.basis[0]);
+
+    let mut congruence = (0, 1);
+    let (mut lo, mut hi) = (0, bounds[free]);
+    for j in 0..p.len() {
+        let l = lcm(p[j].denom(), d[j].denom());
+        let a = d[j].numer() * (l / d[j].denom());
+        let b = -p[j].numer() * (l / p[j].denom());
+        let c = linalg::solve_congruence(a, b, l)?;
+        congruence = linalg::combine_congruences(congruence, c)?;
+
+        // p[j] + t * d[j] >= 0
+        match d[j].cmp(&Rational::ZERO) {
+            std::cmp::Ordering::Greater => lo = lo.max((-p[j] / d[j]).ceil()),
+            std::cmp::Ordering::Less => hi = hi.min((-p[j] / d[j]).floor()),
+            std::cmp::Ordering::Equal if p[j] < Rational::ZERO => return None,
+            std::cmp::Ordering::Equal => {}
+        }
+    }
+    if lo > hi {
         return None;
     }
 
-    // Apply Cramer's Rule to solve for X and B
-    let x_num = b.x * a.d - a.b * b.y; // Numerator for X
-    let b_num = a.a * b.y - b.x * a.c; // Numerator for B
-
-    if x_num % det != 0 || b_num % det != 0 {
-        // Check if x_num and b_num are cleanly divisible by the determinant
-        return None; // No integer solution exists
+    let cost_at = |t: i128| -> Rational {
+        p.iter()
+            .zip(d.iter())
+            .zip(costs.iter())
+            .fold(Rational::ZERO, |acc, ((&p, &d), &c)| {
+                acc + (p + d * Rational::from(t)) * Rational::from(c)
+            })
+    };
+    let slope = cost_at(1) - cost_at(0);
+
+    let (r, m) = congruence;
+    let t = if m == 0 {
+        r
+    } else if slope >= Rational::ZERO {
+        lo + (r - lo).rem_euclid(m)
+    } else {
+        hi - (hi - r).rem_euclid(m)
+    };
+    if t < lo || t > hi {
+        return None;
     }
 
-    let x = x_num / det;
-    let y = b_num / det;
-
-    Some(Vector2 { x, y })
+    cost_at(t).to_integer()
 }
 
-fn validate(matrix: &Matrix2x2, b: &Vector2, solution: &Vector2) -> bool {
-    let x = solution.x;
-    let y = solution.y;
-
-    let x_prime = matrix.a * x + matrix.b * y;
-    let y_prime = matrix.c * x + matrix.d * y;
-
-    (x_prime - b.x).abs() == 0 && (y_prime - b.y).abs() == 0
+fn lcm(a: i128, b: i128) -> i128 {
+    a / linalg::gcd(a, b) * b
 }
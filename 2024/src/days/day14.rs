@@ -1,149 +1,282 @@
+use aoc_core::image::{self, GrayImage, ImageFormat};
+use aoc_core::input::{self, Shape};
+use aoc_core::linalg;
+use aoc_core::runner::{self, answer};
+use aoc_core::visualize::Renderer;
 use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::open("day14/input.txt")?;
-    let reader = BufReader::new(file);
+/// A position and velocity per robot.
+pub const INPUT: Shape = Shape::Lines("p=<int>,<int> v=<int>,<int>");
 
-    let sim: usize = 100;
-    let wide: usize = 101;
-    let tall: usize = 103;
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day14_options")]
+pub struct Options {
+    /// Grid size for day 14 as WIDTHxHEIGHT [default: 101x103, or 11x7 for a sample]
+    #[clap(long, value_parser = parse_grid_size)]
+    pub grid: Option<(i64, i64)>,
 
-    let mut robots = Vec::new();
+    /// Day 14: export the robot grid at these seconds, e.g. `--frames 100,8168`
+    #[clap(long, value_delimiter = ',')]
+    pub frames: Vec<i64>,
 
-    for line in reader.lines() {
-        let line = line?;
+    /// Day 14: export every second in START..END as a numbered image sequence
+    #[clap(long, value_parser = parse_frame_range)]
+    pub frame_range: Option<Range<i64>>,
 
-        let re = Regex::new(r"p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)").unwrap();
+    /// Day 14: export the N seconds with the lowest robot position variance
+    #[clap(long, value_name = "N")]
+    pub best_frames: Option<usize>,
 
-        if let Some(captures) = re.captures(&line) {
-            let p1 = captures.get(1).unwrap().as_str().parse::<i64>().unwrap();
-            let p2 = captures.get(2).unwrap().as_str().parse::<i64>().unwrap();
-            let v1 = captures.get(3).unwrap().as_str().parse::<i64>().unwrap();
-            let v2 = captures.get(4).unwrap().as_str().parse::<i64>().unwrap();
+    /// Day 14: image format for exported frames [default: png]
+    #[clap(long, value_enum)]
+    pub image_format: Option<ImageFormat>,
 
-            robots.push(Robot {
-                position: (p1, p2),
-                velocity: (v1, v2),
-                grid: (wide, tall),
-            });
-        }
+    /// Day 14: directory exported frames are written to [default: day14/frames]
+    #[clap(long)]
+    pub frames_dir: Option<PathBuf>,
+}
+
+impl Options {
+    fn exports(&self) -> bool {
+        !self.frames.is_empty() || self.frame_range.is_some() || self.best_frames.is_some()
     }
+}
 
-    for i in 0..sim {
-        for robot in robots.iter_mut() {
-            robot.simulate();
-        }
-        let (xi, yi) = std_dev(robots.clone());
-        if (xi < 25) && (yi < 25) {
-            println!("{}: {:?}", i, std_dev(robots.clone()));
-        }
+/// The space the robots move in. The puzzle states it rather than the input.
+const GRID: (i64, i64) = (101, 103);
+const SAMPLE_GRID: (i64, i64) = (11, 7);
+
+/// Runs day 14 with an explicit grid size, falling back to the puzzle's when
+/// none is given, and exports any frames that were asked for.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let (options, vis) = (&args.day14, &args.visualize);
+    let robots = read_robots("day14/input.txt")?;
+    let grid = options.grid.unwrap_or(match runner::sample() {
+        Some(_) => SAMPLE_GRID,
+        None => GRID,
+    });
+
+    answer(1, safety_factor(&robots, grid, 100));
+
+    let tree = find_tree(&robots, grid);
+    match tree {
+        Some(t) => answer(2, t),
+        None => println!(
+            "Day 14 Part 2: no solution for a {}x{} grid",
+            grid.0, grid.1
+        ),
     }
 
-    let mut grid = vec![vec![0; wide]; tall];
-    for robot in robots.iter() {
-        let x = robot.position.0 as usize % wide;
-        let y = robot.position.1 as usize % tall;
-        grid[y][x] += 1;
+    if vis.visualize {
+        let mut renderer = Renderer::new(vis.fps);
+        let last = tree.unwrap_or(100);
+        simulate(&robots, grid, 0..last + 1, |t, tiles| {
+            renderer.frame(tiles, &format!("second {}/{}", t, last))
+        });
     }
 
-    let mut quads = vec![0; 4];
+    if options.exports() {
+        export_frames(&robots, grid, options)?;
+    }
 
-    for robot in robots.iter() {
-        let x = robot.position.0 as usize % wide;
-        let y = robot.position.1 as usize % tall;
+    Ok(())
+}
 
-        if x < wide / 2 && y < tall / 2 {
-            quads[0] += 1;
-        } else if x > wide / 2 && y < tall / 2 {
-            quads[1] += 1;
-        } else if x < wide / 2 && y > tall / 2 {
-            quads[2] += 1;
-        } else if x > wide / 2 && y > tall / 2 {
-            quads[3] += 1;
-        }
+/// Parses a grid size given as `WIDTHxHEIGHT`, e.g. `101x103`.
+pub fn parse_grid_size(s: &str) -> Result<(i64, i64), String> {
+    let (w, h) = s
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got `{}`", s))?;
+    let w = w.trim().parse::<i64>().map_err(|e| e.to_string())?;
+    let h = h.trim().parse::<i64>().map_err(|e| e.to_string())?;
+    if w <= 0 || h <= 0 {
+        return Err(format!("grid dimensions must be positive, got `{}`", s));
+    }
+    Ok((w, h))
+}
+
+/// Parses a half-open range of seconds given as `START..END`.
+pub fn parse_frame_range(s: &str) -> Result<Range<i64>, String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected START..END, got `{}`", s))?;
+    let start = start.trim().parse::<i64>().map_err(|e| e.to_string())?;
+    let end = end.trim().parse::<i64>().map_err(|e| e.to_string())?;
+    if start < 0 || end < start {
+        return Err(format!("expected 0 <= START <= END, got `{}`", s));
     }
+    Ok(start..end)
+}
 
-    println!("Day 14 Part 1: {}", product(quads));
+fn read_robots(file_path: &str) -> Result<Vec<Robot>, Box<dyn std::error::Error>> {
+    let content = input::read(file_path)?;
 
-    let mut i = sim;
-    loop {
-        i += 1;
-        for robot in robots.iter_mut() {
-            robot.simulate();
-        }
-        let (xi, yi) = std_dev(robots.clone());
-        if (xi < 25) && (yi < 25) {
-            break;
+    let re = Regex::new(r"p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)")?;
+
+    let mut robots = Vec::new();
+    for line in content.lines() {
+        if let Some(captures) = re.captures(line) {
+            robots.push(Robot {
+                position: (captures[1].parse()?, captures[2].parse()?),
+                velocity: (captures[3].parse()?, captures[4].parse()?),
+            });
         }
     }
-    println!("Day 14 Part 2: {}", i);
 
-    Ok(())
+    Ok(robots)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 struct Robot {
     position: (i64, i64),
     velocity: (i64, i64),
-    grid: (usize, usize),
 }
 
 impl Robot {
-    fn simulate(&mut self) {
-        self.position.0 += self.velocity.0;
-        self.position.1 += self.velocity.1;
+    /// Position of the robot after `t` seconds on a wrapping `grid`.
+    fn at(&self, t: i64, grid: (i64, i64)) -> (i64, i64) {
+        (
+            (self.position.0 + self.velocity.0 * t).rem_euclid(grid.0),
+            (self.position.1 + self.velocity.1 * t).rem_euclid(grid.1),
+        )
+    }
+}
 
-        while self.position.0 < 0 {
-            self.position.0 += self.grid.0 as i64;
-        }
-        while self.position.1 < 0 {
-            self.position.1 += self.grid.1 as i64;
-        }
-        while self.position.0 >= self.grid.0 as i64 {
-            self.position.0 -= self.grid.0 as i64;
-        }
-        while self.position.1 >= self.grid.1 as i64 {
-            self.position.1 -= self.grid.1 as i64;
+/// Calls `on_tick` with a drawing of the robots for every second in `seconds`.
+/// Tiles show how many robots share them.
+fn simulate<F>(robots: &[Robot], grid: (i64, i64), seconds: Range<i64>, mut on_tick: F)
+where
+    F: FnMut(i64, &[Vec<char>]),
+{
+    for t in seconds {
+        let mut tiles = vec![vec!['.'; grid.0 as usize]; grid.1 as usize];
+        for robot in robots {
+            let (x, y) = robot.at(t, grid);
+            let tile = &mut tiles[y as usize][x as usize];
+            *tile = match *tile {
+                '.' => '1',
+                '9' => '9',
+                n => (n as u8 + 1) as char,
+            };
         }
+        on_tick(t, &tiles);
     }
-    // }
 }
 
-fn product(quads: Vec<usize>) -> usize {
-    let mut prod = 1;
-    for quad in quads.iter() {
-        prod *= quad;
+fn safety_factor(robots: &[Robot], grid: (i64, i64), t: i64) -> usize {
+    let (mid_x, mid_y) = (grid.0 / 2, grid.1 / 2);
+    let mut quads = [0; 4];
+
+    for robot in robots {
+        let (x, y) = robot.at(t, grid);
+        if x == mid_x || y == mid_y {
+            continue;
+        }
+        let idx = usize::from(x > mid_x) + 2 * usize::from(y > mid_y);
+        quads[idx] += 1;
     }
-    prod
+
+    quads.iter().product()
+}
+
+/// The tree is the moment the robots are most tightly clustered. Horizontal
+/// motion repeats every `grid.0` seconds and vertical motion every `grid.1`
+/// seconds, so each axis is minimised independently over one period and the two
+/// phases are combined with the Chinese Remainder Theorem.
+fn find_tree(robots: &[Robot], grid: (i64, i64)) -> Option<i64> {
+    let tx = min_variance_time(grid.0, |t| robots.iter().map(move |r| r.at(t, grid).0))?;
+    let ty = min_variance_time(grid.1, |t| robots.iter().map(move |r| r.at(t, grid).1))?;
+    let (t, _) =
+        linalg::combine_congruences((tx as i128, grid.0 as i128), (ty as i128, grid.1 as i128))?;
+    Some(t as i64)
 }
 
-fn std_dev(robots: Vec<Robot>) -> (i64, i64) {
-    // Compute the std dev of the robots
-    let mut pos: Vec<(i64, i64)> = vec![(0, 0); robots.len()];
-    for (i, robot) in robots.iter().enumerate() {
-        pos[i] = robot.position;
+/// Seconds within one full cycle of the robots (`lcm` of the grid sides),
+/// ordered by how tightly the robots are clustered.
+fn rank_by_variance(robots: &[Robot], grid: (i64, i64), n: usize) -> Vec<i64> {
+    let cycle = grid.0 / linalg::gcd(grid.0 as i128, grid.1 as i128) as i64 * grid.1;
+
+    let mut ranked: Vec<(i64, i64)> = (0..cycle)
+        .map(|t| {
+            let vx = variance(robots.iter().map(|r| r.at(t, grid).0));
+            let vy = variance(robots.iter().map(|r| r.at(t, grid).1));
+            (vx + vy, t)
+        })
+        .collect();
+    ranked.sort_unstable();
+    ranked.into_iter().take(n).map(|(_, t)| t).collect()
+}
+
+fn min_variance_time<F, I>(period: i64, coords: F) -> Option<i64>
+where
+    F: Fn(i64) -> I,
+    I: Iterator<Item = i64>,
+{
+    (0..period).min_by_key(|&t| variance(coords(t)))
+}
+
+/// Population variance scaled by `n²` so it stays in integers; the scale is the
+/// same for every `t`, which is all the comparison needs.
+fn variance(values: impl Iterator<Item = i64>) -> i64 {
+    let (mut n, mut sum, mut sum_sq) = (0, 0, 0);
+    for v in values {
+        n += 1;
+        sum += v;
+        sum_sq += v * v;
     }
+    n * sum_sq - sum * sum
+}
 
-    let mut mean = (0, 0);
-    for p in pos.iter() {
-        mean.0 += p.0;
-        mean.1 += p.1;
+fn export_frames(
+    robots: &[Robot],
+    grid: (i64, i64),
+    options: &Options,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = options.image_format.unwrap_or(ImageFormat::Png);
+    let dir = options
+        .frames_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("day14/frames"));
+    fs::create_dir_all(&dir)?;
+
+    let mut seconds = options.frames.clone();
+    if let Some(range) = &options.frame_range {
+        seconds.extend(range.clone());
+    }
+    if let Some(n) = options.best_frames {
+        let best = rank_by_variance(robots, grid, n);
+        println!("Day 14 lowest variance seconds: {:?}", best);
+        seconds.extend(best);
     }
-    mean.0 /= pos.len() as i64;
-    mean.1 /= pos.len() as i64;
+    seconds.sort_unstable();
+    seconds.dedup();
 
-    let mut std_dev = (0, 0);
-    for p in pos.iter() {
-        std_dev.0 += (p.0 - mean.0).pow(2);
-        std_dev.1 += (p.1 - mean.1).pow(2);
+    for &t in seconds.iter() {
+        let path = dir.join(format!("t{:06}.{}", t, format.extension()));
+        image::save(&render(robots, grid, t), format, &path)?;
     }
-    std_dev.0 /= pos.len() as i64;
-    std_dev.1 /= pos.len() as i64;
+    println!(
+        "Day 14 exported {} frame(s) to {}",
+        seconds.len(),
+        dir.display()
+    );
+
+    Ok(())
+}
 
-    std_dev.0 = (std_dev.0 as f64).sqrt() as i64;
-    std_dev.1 = (std_dev.1 as f64).sqrt() as i64;
+/// Draws the robots at second `t`, brighter where several share a tile.
+fn render(robots: &[Robot], grid: (i64, i64), t: i64) -> GrayImage {
+    let mut counts = vec![0u32; (grid.0 * grid.1) as usize];
+    for robot in robots {
+        let (x, y) = robot.at(t, grid);
+        counts[(y * grid.0 + x) as usize] += 1;
+    }
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
 
-    return std_dev;
+    let mut image = GrayImage::new(grid.0 as usize, grid.1 as usize);
+    image.pixels = counts.iter().map(|&c| (c * 255 / max) as u8).collect();
+    image
 }
@@ -1,71 +1,157 @@
-use std::fs;
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+use aoc_core::visualize::Renderer;
 use std::io;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let data = match read_file_to_string("day15/sample.txt") {
-        Ok(content) => content,
-        Err(e) => return Err(e.into()),
-    };
+/// The warehouse map, then the robot's moves.
+pub const INPUT: Shape = Shape::Sections(&[Shape::Grid("#.O@"), Shape::Chars("<>^v")]);
 
-    let (grid, moves) = data.split_once("\n\n").unwrap();
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let vis = &args.visualize;
+    let data = input::read("day15/input.txt")?;
+
+    let (grid, moves) = match data.split_once("\n\n") {
+        Some(parts) => parts,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected a blank line between the map and the moves",
+            )
+            .into())
+        }
+    };
 
-    let grid = parse_grid(grid);
     let moves = parse_moves(moves);
-    let (_x, _y) = location_of(&grid);
 
-    for m in moves {
-        match m {
-            "<" => {
-                println!("left")
-            }
-            ">" => {
-                println!("right")
-            }
-            "^" => {
-                println!("up")
-            }
-            "v" => {
-                println!("down")
-            }
-            _ => println!("unknown"),
+    let mut renderer = vis.visualize.then(|| Renderer::new(vis.fps));
+    let mut on_tick = |grid: &[Vec<char>], step: usize| {
+        if let Some(r) = renderer.as_mut() {
+            r.frame(grid, &format!("move {}/{}", step, moves.len()));
         }
-    }
+    };
 
-    println!("Day 14 Part 1: {}", 0);
+    let mut small = parse_grid(grid);
+    simulate(&mut small, &moves, &mut on_tick);
+    answer(1, gps_sum(&small));
 
-    println!("Day 14 Part 2: {}", 0);
+    let mut wide = widen(&parse_grid(grid));
+    simulate(&mut wide, &moves, &mut on_tick);
+    answer(2, gps_sum(&wide));
 
     Ok(())
 }
 
-fn read_file_to_string(file_path: &str) -> io::Result<String> {
-    fs::read_to_string(file_path)
-}
-
 fn parse_grid(grid: &str) -> Vec<Vec<char>> {
     grid.lines().map(|line| line.chars().collect()).collect()
 }
 
-fn parse_moves(moves: &str) -> Vec<&str> {
-    moves.lines().collect()
+/// Moves are split over several lines in the input, but form one sequence.
+fn parse_moves(moves: &str) -> Vec<(i32, i32)> {
+    moves
+        .chars()
+        .filter_map(|c| match c {
+            '<' => Some((-1, 0)),
+            '>' => Some((1, 0)),
+            '^' => Some((0, -1)),
+            'v' => Some((0, 1)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Part 2 doubles the width of everything except the robot.
+fn widen(grid: &[Vec<char>]) -> Vec<Vec<char>> {
+    grid.iter()
+        .map(|row| {
+            row.iter()
+                .flat_map(|c| match c {
+                    '#' => ['#', '#'],
+                    'O' => ['[', ']'],
+                    '@' => ['@', '.'],
+                    _ => ['.', '.'],
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Applies every move to the warehouse, calling `on_tick` with the grid and the
+/// number of moves made after each one.
+fn simulate<F>(grid: &mut [Vec<char>], moves: &[(i32, i32)], on_tick: &mut F)
+where
+    F: FnMut(&[Vec<char>], usize),
+{
+    let mut robot = location_of(grid);
+    on_tick(grid, 0);
+
+    for (step, &(dx, dy)) in moves.iter().enumerate() {
+        if let Some(pushed) = pushed_cells(grid, robot, (dx, dy)) {
+            // Move the furthest cells first so nothing is overwritten.
+            for &(x, y) in pushed.iter().rev() {
+                let (nx, ny) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+                grid[ny][nx] = grid[y][x];
+                grid[y][x] = '.';
+            }
+            robot = (
+                (robot.0 as i32 + dx) as usize,
+                (robot.1 as i32 + dy) as usize,
+            );
+        }
+
+        on_tick(grid, step + 1);
+    }
+}
+
+/// Every cell that has to move for the robot to step in `(dx, dy)`, in the
+/// order they were reached, or `None` if a wall is in the way. Vertical pushes
+/// of wide boxes can fan out, so this is a breadth-first search.
+fn pushed_cells(
+    grid: &[Vec<char>],
+    robot: (usize, usize),
+    (dx, dy): (i32, i32),
+) -> Option<Vec<(usize, usize)>> {
+    let mut cells = vec![robot];
+    let mut i = 0;
+
+    while i < cells.len() {
+        let (x, y) = cells[i];
+        i += 1;
+
+        let (nx, ny) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+        let mut next = match grid[ny][nx] {
+            '#' => return None,
+            'O' => vec![(nx, ny)],
+            '[' if dy != 0 => vec![(nx, ny), (nx + 1, ny)],
+            ']' if dy != 0 => vec![(nx, ny), (nx - 1, ny)],
+            '[' | ']' => vec![(nx, ny)],
+            _ => vec![],
+        };
+        next.retain(|cell| !cells.contains(cell));
+        cells.extend(next);
+    }
+
+    Some(cells)
 }
 
-fn location_of(grid: &Vec<Vec<char>>) -> (usize, usize) {
+fn gps_sum(grid: &[Vec<char>]) -> usize {
+    let mut sum = 0;
     for (y, row) in grid.iter().enumerate() {
         for (x, cell) in row.iter().enumerate() {
-            if *cell == '@' {
-                return (x, y);
+            if *cell == 'O' || *cell == '[' {
+                sum += 100 * y + x;
             }
         }
     }
-    (0, 0)
+    sum
 }
 
-fn _print_grid(grid: &Vec<Vec<char>>) {
-    for row in grid {
-        for cell in row {
-            print!("{}", cell);
+fn location_of(grid: &[Vec<char>]) -> (usize, usize) {
+    for (y, row) in grid.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if *cell == '@' {
+                return (x, y);
+            }
         }
-        println!();
     }
+    (0, 0)
 }
@@ -0,0 +1,168 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::io;
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The reindeer maze.
+pub const INPUT: Shape = Shape::Grid("#.SE");
+
+/// Reindeer maze. Using `day16/sample.txt` gives 7036 and 45, and
+/// `day16/sample2.txt` gives 11048 and 64.
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let data = input::read("day16/input.txt")?;
+    let maze = Maze::parse(&data)?;
+
+    let from_start = maze.dijkstra(&[(maze.start, EAST)], false);
+    let Some(best) = maze.best_score(&from_start) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "no path to the end tile").into());
+    };
+
+    answer(1, best);
+    answer(2, maze.tiles_on_best_paths(&from_start, best));
+
+    Ok(())
+}
+
+const EAST: usize = 0;
+const DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const STEP_COST: u64 = 1;
+const TURN_COST: u64 = 1000;
+
+struct Maze {
+    walls: Vec<Vec<bool>>,
+    width: usize,
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+impl Maze {
+    fn parse(data: &str) -> Result<Maze, io::Error> {
+        let mut walls = Vec::new();
+        let (mut start, mut end) = (None, None);
+        for (y, line) in data.lines().enumerate() {
+            let mut row = Vec::new();
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    'S' => start = Some((x, y)),
+                    'E' => end = Some((x, y)),
+                    _ => {}
+                }
+                row.push(c == '#');
+            }
+            walls.push(row);
+        }
+
+        let missing = |what| io::Error::new(io::ErrorKind::InvalidData, what);
+        Ok(Maze {
+            width: walls.first().map_or(0, |r| r.len()),
+            walls,
+            start: start.ok_or_else(|| missing("maze has no start tile"))?,
+            end: end.ok_or_else(|| missing("maze has no end tile"))?,
+        })
+    }
+
+    fn state(&self, (x, y): (usize, usize), dir: usize) -> usize {
+        (y * self.width + x) * 4 + dir
+    }
+
+    fn step(&self, (x, y): (usize, usize), dir: usize) -> Option<(usize, usize)> {
+        let (dx, dy) = DIRECTIONS[dir];
+        let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+        if nx < 0 || ny < 0 {
+            return None;
+        }
+        let (nx, ny) = (nx as usize, ny as usize);
+        match self.walls.get(ny).and_then(|row| row.get(nx)) {
+            Some(false) => Some((nx, ny)),
+            _ => None,
+        }
+    }
+
+    /// Cheapest cost to reach every (tile, facing) state from `sources`. With
+    /// `reverse` set, moves are walked backwards, which gives the cost from each
+    /// state to the sources instead.
+    fn dijkstra(&self, sources: &[((usize, usize), usize)], reverse: bool) -> Vec<u64> {
+        let mut dist = vec![u64::MAX; self.walls.len() * self.width * 4];
+        let mut heap = BinaryHeap::new();
+        for &(pos, dir) in sources {
+            dist[self.state(pos, dir)] = 0;
+            heap.push(Reverse((0, pos, dir)));
+        }
+
+        while let Some(Reverse((cost, pos, dir))) = heap.pop() {
+            if cost > dist[self.state(pos, dir)] {
+                continue;
+            }
+
+            let forward = if reverse { (dir + 2) % 4 } else { dir };
+            let mut next = vec![
+                (pos, (dir + 1) % 4, cost + TURN_COST),
+                (pos, (dir + 3) % 4, cost + TURN_COST),
+            ];
+            if let Some(p) = self.step(pos, forward) {
+                next.push((p, dir, cost + STEP_COST));
+            }
+
+            for (p, d, c) in next {
+                let s = self.state(p, d);
+                if c < dist[s] {
+                    dist[s] = c;
+                    heap.push(Reverse((c, p, d)));
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// The lowest score that reaches the end tile facing any way.
+    fn best_score(&self, from_start: &[u64]) -> Option<u64> {
+        (0..4)
+            .map(|d| from_start[self.state(self.end, d)])
+            .filter(|&cost| cost != u64::MAX)
+            .min()
+    }
+
+    /// A tile is on a best path when some facing has cost-from-start plus
+    /// cost-to-end equal to the best score.
+    fn tiles_on_best_paths(&self, from_start: &[u64], best: u64) -> usize {
+        let ends: Vec<_> = (0..4).map(|d| (self.end, d)).collect();
+        let to_end = self.dijkstra(&ends, true);
+
+        let mut tiles = HashSet::new();
+        for y in 0..self.walls.len() {
+            for x in 0..self.width {
+                for d in 0..4 {
+                    let s = self.state((x, y), d);
+                    if from_start[s] != u64::MAX
+                        && to_end[s] != u64::MAX
+                        && from_start[s] + to_end[s] == best
+                    {
+                        tiles.insert((x, y));
+                    }
+                }
+            }
+        }
+        tiles.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(data: &str) -> (u64, usize) {
+        let maze = Maze::parse(data).unwrap();
+        let from_start = maze.dijkstra(&[(maze.start, EAST)], false);
+        let best = maze.best_score(&from_start).unwrap();
+        (best, maze.tiles_on_best_paths(&from_start, best))
+    }
+
+    #[test]
+    fn samples() {
+        assert_eq!(solve(include_str!("../../day16/sample.txt")), (7036, 45));
+        assert_eq!(solve(include_str!("../../day16/sample2.txt")), (11048, 64));
+    }
+}
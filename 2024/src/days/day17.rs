@@ -1,9 +1,20 @@
 use regex::Regex;
-use std::fs;
 use std::io;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let (registers, opcodes) = match read_and_split_file("day17/input.txt") {
+use aoc_core::input::{self, Shape};
+
+/// The registers, then the program.
+pub const INPUT: Shape = Shape::Sections(&[
+    Shape::Rows(&[
+        "Register A: <int>",
+        "Register B: <int>",
+        "Register C: <int>",
+    ]),
+    Shape::Line("Program: <ints,>"),
+]);
+
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let (registers, opcodes) = match input::read_sections("day17/input.txt") {
         Ok((a, b)) => (a, b),
         Err(e) => return Err(e.into()),
     };
@@ -75,18 +86,15 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         instruction_pointer += 2;
     }
 
-    println!(
-        "Day 17 Part 1: {}",
+    answer(
+        1,
         res.iter()
             .map(|num| num.to_string())
             .collect::<Vec<String>>()
-            .join(",")
+            .join(","),
     );
 
-    println!(
-        "Day 17 Part 2: {}",
-        run_on_loop(opcodes, a_reg, b_reg, c_reg)
-    );
+    answer(2, run_on_loop(opcodes, a_reg, b_reg, c_reg));
     Ok(())
 }
 
@@ -134,6 +142,8 @@ fn eval(a: usize, b: usize, c: usize, program: Vec<usize>) -> usize {
 
 use std::mem;
 
+use aoc_core::runner::answer;
+
 fn run_on_loop(program: Vec<usize>, _a: usize, b: usize, c: usize) -> usize {
     let target = program.iter().fold(0, |acc, digit| acc * 10 + digit);
 
@@ -159,17 +169,6 @@ fn run_on_loop(program: Vec<usize>, _a: usize, b: usize, c: usize) -> usize {
     p2
 }
 
-fn read_and_split_file(file_path: &str) -> io::Result<(String, String)> {
-    let content = fs::read_to_string(file_path)?;
-    if let Some((part1, part2)) = content.split_once("\n\n") {
-        Ok((part1.to_string(), part2.to_string()))
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Delimiter not found",
-        ))
-    }
-}
 fn extract_register_values(input: &str) -> Option<(usize, usize, usize)> {
     let re = Regex::new(r"Register A: (\d+)\s+Register B: (\d+)\s+Register C: (\d+)").unwrap();
     if let Some(captures) = re.captures(input) {
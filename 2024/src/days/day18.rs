@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+use std::io;
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::{self, answer};
+
+/// One falling byte position per line.
+pub const INPUT: Shape = Shape::Lines("<uint>,<uint>");
+
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day18_options")]
+pub struct Options {
+    /// Day 18: width and height of the memory space [default: 71, or 7 for a
+    /// sample]
+    #[clap(long)]
+    pub memory_size: Option<usize>,
+
+    /// Day 18: bytes fallen when part 1 looks for a path [default: 1024, or
+    /// 12 for a sample]
+    #[clap(long)]
+    pub fallen: Option<usize>,
+}
+
+/// The puzzle's memory space and bytes fallen for part 1, and the sample's.
+const SPACE: (usize, usize) = (71, 1024);
+const SAMPLE_SPACE: (usize, usize) = (7, 12);
+
+/// RAM run. Using `day18/sample.txt` gives 22 and 6,1.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day18;
+    let data = input::read("day18/input.txt")?;
+    let bytes = parse_bytes(&data)?;
+
+    // The input doesn't say how big the space is, only the puzzle does.
+    let (size, fallen) = match runner::sample() {
+        Some(_) => SAMPLE_SPACE,
+        None => SPACE,
+    };
+    let size = options.memory_size.unwrap_or(size);
+    let fallen = options.fallen.unwrap_or(fallen);
+    if let Some(&(x, y)) = bytes.iter().find(|&&(x, y)| x.max(y) >= size) {
+        return Err(format!(
+            "byte {},{} is outside the {}x{} memory space",
+            x, y, size, size
+        )
+        .into());
+    }
+
+    match shortest_path(&bytes[..fallen.min(bytes.len())], size) {
+        Some(steps) => answer(1, steps),
+        None => println!("Day 18 Part 1: no path"),
+    }
+
+    match first_blocking_byte(&bytes, size) {
+        Some((x, y)) => answer(2, format!("{},{}", x, y)),
+        None => println!("Day 18 Part 2: the exit is never cut off"),
+    }
+
+    Ok(())
+}
+
+fn parse_bytes(data: &str) -> Result<Vec<(usize, usize)>, Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    for line in data.lines().filter(|l| !l.trim().is_empty()) {
+        let (x, y) = line.trim().split_once(',').ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected X,Y: {}", line),
+            )
+        })?;
+        bytes.push((x.parse()?, y.parse()?));
+    }
+    Ok(bytes)
+}
+
+/// Fewest steps from the top left to the bottom right corner, avoiding the
+/// corrupted `bytes`.
+fn shortest_path(bytes: &[(usize, usize)], size: usize) -> Option<usize> {
+    let mut corrupted = vec![vec![false; size]; size];
+    for &(x, y) in bytes {
+        corrupted[y][x] = true;
+    }
+    if corrupted[0][0] {
+        return None;
+    }
+
+    let mut dist = vec![vec![usize::MAX; size]; size];
+    let mut queue = VecDeque::new();
+    dist[0][0] = 0;
+    queue.push_back((0, 0));
+
+    while let Some((x, y)) = queue.pop_front() {
+        if (x, y) == (size - 1, size - 1) {
+            return Some(dist[y][x]);
+        }
+        for (nx, ny) in neighbours(x, y, size) {
+            if !corrupted[ny][nx] && dist[ny][nx] == usize::MAX {
+                dist[ny][nx] = dist[y][x] + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    None
+}
+
+/// Binary search for the first byte after which the exit is unreachable.
+fn first_blocking_byte(bytes: &[(usize, usize)], size: usize) -> Option<(usize, usize)> {
+    if shortest_path(bytes, size).is_some() {
+        return None;
+    }
+
+    // Invariant: reachable with `lo` bytes fallen, unreachable with `hi`.
+    let (mut lo, mut hi) = (0, bytes.len());
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if shortest_path(&bytes[..mid], size).is_some() {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Some(bytes[hi - 1])
+}
+
+fn neighbours(x: usize, y: usize, size: usize) -> impl Iterator<Item = (usize, usize)> {
+    [(0, 1), (1, 0), (0, -1), (-1, 0)]
+        .into_iter()
+        .filter_map(move |(dx, dy): (i64, i64)| {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            (nx >= 0 && ny >= 0 && nx < size as i64 && ny < size as i64)
+                .then_some((nx as usize, ny as usize))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let bytes = parse_bytes(include_str!("../../day18/sample.txt")).unwrap();
+        assert_eq!(shortest_path(&bytes[..12], 7), Some(22));
+        assert_eq!(first_blocking_byte(&bytes, 7), Some((6, 1)));
+    }
+}
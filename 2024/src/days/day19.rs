@@ -1,9 +1,13 @@
 use std::collections::HashSet;
-use std::fs;
-use std::io;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let (p, designs) = match read_and_split_file("day19/sample.txt") {
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The towel patterns, then one design per line.
+pub const INPUT: Shape = Shape::Sections(&[Shape::Line("<words,>"), Shape::Chars("wubrg")]);
+
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let (p, designs) = match input::read_sections("day19/sample.txt") {
         Ok((a, b)) => (a, b),
         Err(e) => return Err(e.into()),
     };
@@ -21,9 +25,9 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         break;
     }
 
-    println!("Day 19 Part 1: {}", count);
+    answer(1, count);
 
-    println!("Day 19 Part 2: {}", total);
+    answer(2, total);
     Ok(())
 }
 
@@ -65,15 +69,3 @@ fn total_combos(design: &str, patterns: &HashSet<String>) -> u32 {
 
     total
 }
-
-fn read_and_split_file(file_path: &str) -> io::Result<(String, String)> {
-    let content = fs::read_to_string(file_path)?;
-    if let Some((part1, part2)) = content.split_once("\n\n") {
-        Ok((part1.to_string(), part2.to_string()))
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Delimiter not found",
-        ))
-    }
-}
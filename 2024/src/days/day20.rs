@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+use std::io;
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The racetrack.
+pub const INPUT: Shape = Shape::Grid("#.SE");
+
+#[derive(clap::Args, Debug, Clone)]
+#[group(id = "day20_options")]
+pub struct Options {
+    /// Day 20: only count cheats that save at least this many picoseconds
+    #[clap(long, default_value_t = 100)]
+    pub min_saving: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { min_saving: 100 }
+    }
+}
+
+/// Race condition. Using `day20/sample.txt` with `--min-saving 64` gives 1 and
+/// 86, and with `--min-saving 76` gives 0 and 3.
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day20;
+    let data = input::read("day20/input.txt")?;
+    let grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
+
+    let track = race_track(&grid)?;
+
+    answer(1, count_cheats(&track, 2, options.min_saving));
+    answer(2, count_cheats(&track, 20, options.min_saving));
+
+    Ok(())
+}
+
+fn find(grid: &[Vec<char>], target: char) -> Option<(usize, usize)> {
+    grid.iter()
+        .enumerate()
+        .find_map(|(y, row)| row.iter().position(|&c| c == target).map(|x| (x, y)))
+}
+
+type Track = Vec<((usize, usize), usize)>;
+
+/// Every track tile with its distance from the start, in order along the
+/// track. The puzzle promises a single path, but a BFS doesn't rely on it.
+fn race_track(grid: &[Vec<char>]) -> Result<Track, io::Error> {
+    let invalid = |what| io::Error::new(io::ErrorKind::InvalidData, what);
+    let start = find(grid, 'S').ok_or_else(|| invalid("track has no start"))?;
+    let end = find(grid, 'E').ok_or_else(|| invalid("track has no end"))?;
+
+    let mut dist = vec![vec![usize::MAX; grid[0].len()]; grid.len()];
+    let mut queue = VecDeque::new();
+    let mut track = Vec::new();
+    dist[start.1][start.0] = 0;
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        track.push(((x, y), dist[y][x]));
+        for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let (nx, ny) = ((x as i64 + dx) as usize, (y as i64 + dy) as usize);
+            let open = grid
+                .get(ny)
+                .and_then(|row| row.get(nx))
+                .is_some_and(|&c| c != '#');
+            if open && dist[ny][nx] == usize::MAX {
+                dist[ny][nx] = dist[y][x] + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    if dist[end.1][end.0] == usize::MAX {
+        return Err(invalid("the end is not reachable"));
+    }
+    Ok(track)
+}
+
+/// A cheat jumps between two track tiles at most `max_len` apart (Manhattan
+/// distance) and saves the difference in their track distance minus the jump.
+fn count_cheats(track: &[((usize, usize), usize)], max_len: usize, min_saving: usize) -> usize {
+    let mut count = 0;
+    for (i, &((x1, y1), d1)) in track.iter().enumerate() {
+        for &((x2, y2), d2) in track[i + 1..].iter() {
+            let jump = x1.abs_diff(x2) + y1.abs_diff(y2);
+            if jump <= max_len && d2 >= d1 + jump + min_saving {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let data = include_str!("../../day20/sample.txt");
+        let grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
+        let track = race_track(&grid).unwrap();
+        assert_eq!(count_cheats(&track, 2, 64), 1);
+        assert_eq!(count_cheats(&track, 20, 64), 86);
+        assert_eq!(count_cheats(&track, 2, 76), 0);
+        assert_eq!(count_cheats(&track, 20, 76), 3);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::io;
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// One door code per line.
+pub const INPUT: Shape = Shape::Lines("<word>");
+
+/// Keypad conundrum. Using `day21/sample.txt` gives 126384 and 154115708116294.
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let data = input::read("day21/input.txt")?;
+    let codes: Vec<&str> = data.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    let mut keypads = Keypads::new();
+
+    answer(1, keypads.complexity(&codes, 2)?);
+    answer(2, keypads.complexity(&codes, 25)?);
+
+    Ok(())
+}
+
+const NUMERIC: [&str; 4] = ["789", "456", "123", " 0A"];
+const DIRECTIONAL: [&str; 2] = [" ^A", "<v>"];
+
+fn key_positions(layout: &[&str]) -> HashMap<char, (i64, i64)> {
+    let mut keys = HashMap::new();
+    for (y, row) in layout.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            keys.insert(c, (x as i64, y as i64));
+        }
+    }
+    keys
+}
+
+struct Keypads {
+    numeric: HashMap<char, (i64, i64)>,
+    directional: HashMap<char, (i64, i64)>,
+    /// Presses a human needs to make the robot at `depth` directional keypads
+    /// up move from one key to another and press it.
+    memo: HashMap<(char, char, usize), u64>,
+}
+
+impl Keypads {
+    fn new() -> Keypads {
+        Keypads {
+            numeric: key_positions(&NUMERIC),
+            directional: key_positions(&DIRECTIONAL),
+            memo: HashMap::new(),
+        }
+    }
+
+    fn complexity(&mut self, codes: &[&str], robots: usize) -> Result<u64, io::Error> {
+        let mut total = 0;
+        for code in codes {
+            let numeric_part: u64 = code
+                .trim_end_matches('A')
+                .parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, code.to_string()))?;
+            total += self.presses(code, robots)? * numeric_part;
+        }
+        Ok(total)
+    }
+
+    /// Presses needed on the human's keypad to type `code` on the numeric
+    /// keypad through `robots` directional keypads.
+    fn presses(&mut self, code: &str, robots: usize) -> Result<u64, io::Error> {
+        let mut total = 0;
+        let mut from = 'A';
+        for to in code.chars() {
+            let paths = paths(&self.numeric, from, to)?;
+            total += paths
+                .iter()
+                .map(|p| self.sequence_cost(p, robots))
+                .min()
+                .unwrap_or(0);
+            from = to;
+        }
+        Ok(total)
+    }
+
+    /// Cost of typing `sequence` on a directional keypad `depth` robots away
+    /// from the human. Every robot starts and ends on `A`.
+    fn sequence_cost(&mut self, sequence: &str, depth: usize) -> u64 {
+        if depth == 0 {
+            return sequence.len() as u64;
+        }
+        let mut total = 0;
+        let mut from = 'A';
+        for to in sequence.chars() {
+            total += self.move_cost(from, to, depth);
+            from = to;
+        }
+        total
+    }
+
+    fn move_cost(&mut self, from: char, to: char, depth: usize) -> u64 {
+        if let Some(&cost) = self.memo.get(&(from, to, depth)) {
+            return cost;
+        }
+        let candidates =
+            paths(&self.directional, from, to).expect("directional keys are always known");
+        let cost = candidates
+            .iter()
+            .map(|p| self.sequence_cost(p, depth - 1))
+            .min()
+            .unwrap_or(0);
+        self.memo.insert((from, to, depth), cost);
+        cost
+    }
+}
+
+/// The button sequences (ending in `A`) worth considering to move from one
+/// key to another: all horizontal moves then all vertical ones, or the other
+/// way round, skipping any that would pass over the gap. Zig-zagging is never
+/// cheaper, since repeated presses of the same button cost one each.
+fn paths(keys: &HashMap<char, (i64, i64)>, from: char, to: char) -> Result<Vec<String>, io::Error> {
+    let unknown = |c: char| io::Error::new(io::ErrorKind::InvalidData, format!("no key {}", c));
+    let &(x1, y1) = keys.get(&from).ok_or_else(|| unknown(from))?;
+    let &(x2, y2) = keys.get(&to).ok_or_else(|| unknown(to))?;
+    let gap = keys[&' '];
+
+    let horizontal = if x2 > x1 { ">" } else { "<" }.repeat(x1.abs_diff(x2) as usize);
+    let vertical = if y2 > y1 { "v" } else { "^" }.repeat(y1.abs_diff(y2) as usize);
+
+    let mut paths = Vec::new();
+    if (x2, y1) != gap {
+        paths.push(format!("{}{}A", horizontal, vertical));
+    }
+    if (x1, y2) != gap {
+        paths.push(format!("{}{}A", vertical, horizontal));
+    }
+    paths.dedup();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let codes: Vec<&str> = include_str!("../../day21/sample.txt").lines().collect();
+        let mut keypads = Keypads::new();
+        assert_eq!(keypads.complexity(&codes, 2).unwrap(), 126384);
+        assert_eq!(keypads.complexity(&codes, 25).unwrap(), 154115708116294);
+    }
+}
@@ -1,73 +1,216 @@
-use std::fs;
+use std::collections::BTreeMap;
+use std::thread;
+
+use aoc_core::gf2::BitMatrix;
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// One initial secret number per buyer.
+pub const INPUT: Shape = Shape::Lines("<uint>");
+
+#[derive(clap::Args, Debug, Default, Clone)]
+#[group(id = "day22_options")]
+pub struct Options {
+    /// Day 22: also report the secret sum after this many steps, jumping ahead
+    /// with the step matrix. Negative counts step backwards.
+    #[clap(long, allow_hyphen_values = true)]
+    pub secret_steps: Option<i64>,
+    /// Day 22: print the period of the secret function and of each buyer
+    #[clap(long)]
+    pub secret_cycles: bool,
+}
+
+pub fn run(args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let options = &args.day22;
+    let input = input::read("day22/input.txt")?;
+    let numbers = parse_numbers(&input)?;
+    let step = step_matrix();
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let input = fs::read_to_string("day22/input.txt")?;
-    let numbers = parse_numbers(&input);
+    answer(1, part1(&step, &numbers));
 
-    // let sum: i64 = numbers.iter().map(|&x| get_next(x, 2000)).sum();
+    let (bananas, sequence) = part2(&numbers);
+    answer(2, bananas);
+    println!("Day 22 best price changes: {:?}", sequence);
 
-    println!("Day 22 Part 1: {}", part1(numbers.clone()));
+    if let Some(steps) = options.secret_steps {
+        println!(
+            "Day 22 secret sum after {} steps: {}",
+            steps,
+            sum_after(&step, &numbers, steps)
+        );
+    }
 
-    println!("Day 22 Part 2: {}", part2(numbers.clone()));
+    if options.secret_cycles {
+        print_cycles(&step, &numbers);
+    }
 
     Ok(())
 }
 
-fn parse_numbers(input: &str) -> Vec<i64> {
+fn parse_numbers(input: &str) -> Result<Vec<u32>, std::num::ParseIntError> {
     input
         .lines()
-        .map(|line| line.trim().parse::<i64>().unwrap())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim().parse::<u32>())
         .collect()
 }
 
-fn part1(secrets: Vec<i64>) -> i64 {
-    secrets
-        .iter()
-        .map(|&secret| {
-            let mut result = secret;
+/// Number of secrets stepped together. Each step is the same shifts and XORs
+/// for every lane, so the compiler can turn the inner loops into vector ops.
+const LANES: usize = 8;
+const STEPS: usize = 2000;
 
-            for _ in 0..2000 {
-                result = encode(result);
-            }
+/// Four price changes packed as 5 bits each, see `part2`.
+const WINDOW: usize = 1 << 20;
+
+fn part1(step: &BitMatrix, secrets: &[u32]) -> u64 {
+    sum_after(step, secrets, STEPS as i64)
+}
+
+/// Bits in a secret; every step is taken modulo 2^24.
+const SECRET_BITS: usize = 24;
 
-            result as i64
-        })
-        .sum::<i64>()
+/// Each step is shifts, XORs and a mask, so it is linear over GF(2) and the
+/// whole step is a 24x24 bit matrix.
+fn step_matrix() -> BitMatrix {
+    BitMatrix::from_linear_map(SECRET_BITS, |n| encode(n as u32) as u64)
 }
 
-fn part2(secrets: Vec<i64>) -> i64 {
+/// `steps` steps at once: the step matrix is raised to that power (or its
+/// inverse's, going backwards) once, then each buyer is a single product.
+fn jump(step: &BitMatrix, steps: i64) -> BitMatrix {
+    if steps < 0 {
+        let back = step.inverse().expect("the secret step is invertible");
+        back.pow(steps.unsigned_abs())
+    } else {
+        step.pow(steps as u64)
+    }
+}
+
+fn sum_after(step: &BitMatrix, secrets: &[u32], steps: i64) -> u64 {
+    let m = jump(step, steps);
+    secrets.iter().map(|&s| m.apply(s as u64)).sum()
+}
+
+/// The step is invertible, so it has some order, but nothing makes that
+/// divide any particular number. 2^24 - 1 is tried, being the order when the
+/// step's characteristic polynomial is primitive; if the step's order doesn't
+/// divide it, no cycle lengths are printed. Each buyer cycles with a divisor
+/// of the step's order.
+fn print_cycles(step: &BitMatrix, secrets: &[u32]) {
+    let candidate = (1u64 << SECRET_BITS) - 1;
+    let Some(order) = step.order_dividing(candidate) else {
+        println!(
+            "Day 22 secret step order does not divide {}; no cycle lengths",
+            candidate
+        );
+        return;
+    };
+    println!("Day 22 secret step order: {}", order);
+
+    let cycles = step
+        .periods(order)
+        .expect("the step's order is a multiple of itself");
+    let mut periods = BTreeMap::new();
+    for &s in secrets {
+        *periods.entry(cycles.of(s as u64)).or_insert(0usize) += 1;
+    }
+    for (period, buyers) in periods {
+        println!("Day 22 buyers with period {}: {}", period, buyers);
+    }
+}
+
+/// Returns the most bananas that can be bought and the sequence of four price
+/// changes that buys them.
+fn part2(secrets: &[u32]) -> (u32, [i8; 4]) {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let per_thread = secrets.len().div_ceil(threads).max(1);
+
+    // Each thread fills its own table of bananas per delta window, and the
+    // tables are summed at the end.
+    let tables: Vec<Vec<u32>> = thread::scope(|scope| {
+        let handles: Vec<_> = secrets
+            .chunks(per_thread)
+            .map(|chunk| scope.spawn(move || price_table(chunk)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut costs = vec![0u32; WINDOW];
+    for table in tables.iter() {
+        for (total, &c) in costs.iter_mut().zip(table.iter()) {
+            *total += c;
+        }
+    }
+
+    let (best, &bananas) = costs.iter().enumerate().max_by_key(|&(_, &c)| c).unwrap();
+    (bananas, decode_window(best))
+}
+
+/// Bananas each delta window would buy from the given buyers.
+fn price_table(secrets: &[u32]) -> Vec<u32> {
     // store state in vectors instead of hashmaps/hashsets
-    let mut costs = vec![0; 0xFFFFF];
-    let mut seen = vec![0; 0xFFFFF];
-
-    for secret in secrets {
-        let mut result = secret;
-        let mut previous_cost = result % 10;
-        let mut deltas = 0;
-
-        for i in 0..2000 {
-            result = encode(result);
-            let cost = result % 10;
-
-            // offset cost delta by +10 and represent as 5 bit unsigned int (max == 19 == 0b10101)
-            // store sliding window of 4 deltas as a 20 bit unsigned int (max == 0xFFFFF)
-            deltas = ((deltas << 5) & 0xFFFFF) + 10 + cost - previous_cost;
-
-            // start checking prices once deltas window is populated
-            // only counting the first occurance of each unique delta sequence
-            if seen[deltas as usize] != secret && i > 3 {
-                seen[deltas as usize] = secret;
-                costs[deltas as usize] += cost;
+    let mut costs = vec![0u32; WINDOW];
+    // Buyers are walked a chunk of lanes at a time, so `seen[w]` holds the
+    // chunk that last used window `w` in its upper bits and which of its buyers
+    // (one bit per lane) in the low `LANES` bits. Marking by buyer rather than
+    // by secret keeps buyers with the same starting secret apart.
+    let mut seen = vec![0u32; WINDOW];
+
+    for (c, chunk) in secrets.chunks(LANES).enumerate() {
+        let chunk_marker = ((c + 1) as u32) << LANES;
+        let mut lanes = [0u32; LANES];
+        lanes[..chunk.len()].copy_from_slice(chunk);
+
+        let mut previous = lanes.map(|s| s % 10);
+        let mut deltas = [0u32; LANES];
+
+        for i in 0..STEPS {
+            encode_lanes(&mut lanes);
+
+            for lane in 0..chunk.len() {
+                let cost = lanes[lane] % 10;
+
+                // offset cost delta by +10 and represent as 5 bit unsigned int (max == 19 == 0b10011)
+                // store sliding window of 4 deltas as a 20 bit unsigned int (max == 0xFFFFF)
+                deltas[lane] = ((deltas[lane] << 5) & 0xFFFFF) + 10 + cost - previous[lane];
+                previous[lane] = cost;
+
+                // start checking prices once deltas window is populated
+                // only counting the first occurance of each unique delta sequence
+                let window = deltas[lane] as usize;
+                if i > 2 {
+                    if seen[window] >> LANES != chunk_marker >> LANES {
+                        seen[window] = chunk_marker;
+                    }
+                    let bit = 1 << lane;
+                    if seen[window] & bit == 0 {
+                        seen[window] |= bit;
+                        costs[window] += cost;
+                    }
+                }
             }
-
-            previous_cost = cost;
         }
     }
 
-    costs.iter().max().cloned().unwrap()
+    costs
+}
+
+fn decode_window(window: usize) -> [i8; 4] {
+    let mut changes = [0i8; 4];
+    for (i, change) in changes.iter_mut().enumerate() {
+        *change = ((window >> (5 * (3 - i))) & 0x1F) as i8 - 10;
+    }
+    changes
+}
+
+fn encode_lanes(lanes: &mut [u32; LANES]) {
+    for n in lanes.iter_mut() {
+        *n = encode(*n);
+    }
 }
 
-fn encode(n: i64) -> i64 {
+fn encode(n: u32) -> u32 {
     // this function is a simplification of operations:
     //
     // n ^= n * 64    --> operand is base 2, can be done by bit shifting left by log2(64) == 6
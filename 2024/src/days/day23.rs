@@ -1,11 +1,15 @@
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let input = fs::read_to_string("day23/sample.txt")?;
+use aoc_core::input::{self, Shape};
+
+/// One connection between two computers per line.
+pub const INPUT: Shape = Shape::Lines("<word>-<word>");
+
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let input = input::read("day23/sample.txt")?;
 
     let pairs: Vec<(&str, &str)> = input
-        .split("\n")
+        .lines()
         .map(|line| {
             let mut parts = line.split("-");
             let left = parts.next().unwrap();
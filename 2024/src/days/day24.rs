@@ -1,7 +1,15 @@
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::fs;
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The initial wire values, then the gates.
+pub const INPUT: Shape = Shape::Sections(&[
+    Shape::Lines("<word>: <uint>"),
+    Shape::Lines("<word> <word> <word> -> <word>"),
+]);
 
 #[derive(Debug)]
 struct Instruction {
@@ -29,8 +37,8 @@ impl fmt::Display for Op {
     }
 }
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let input = fs::read_to_string("day24/input.txt")?;
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let input = input::read("day24/input.txt")?;
 
     let (registers, instructions_str) = input.split_once("\n\n").unwrap();
 
@@ -143,9 +151,9 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         val += value * 2_i64.pow(i as u32);
     }
 
-    println!("Day 23 Part 1: {}", val);
+    answer(1, val);
 
-    println!("Day 23 Part 2: {}", 0);
+    println!("Day 24 Part 2: {}", 0);
 
     Ok(())
 }
@@ -0,0 +1,83 @@
+use std::io;
+
+use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// The lock and key schematics.
+pub const INPUT: Shape = Shape::Repeated(&Shape::Grid("#."));
+
+/// Code chronicle. Using `day25/sample.txt` gives 3. There is no second part on
+/// the last day.
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let data = input::read("day25/input.txt")?;
+    let (locks, keys) = parse_schematics(&data)?;
+
+    answer(1, count_fits(&locks, &keys));
+
+    Ok(())
+}
+
+/// Schematics are 5 columns wide and 7 rows tall; the top and bottom rows are
+/// the base, leaving 5 rows of pins.
+const PIN_SPACE: u8 = 5;
+
+/// Column heights of a lock or key.
+type Heights = Vec<u8>;
+
+/// Splits the schematics into locks (top row filled) and keys (bottom row
+/// filled), each described by its column heights.
+fn parse_schematics(data: &str) -> Result<(Vec<Heights>, Vec<Heights>), io::Error> {
+    let (mut locks, mut keys) = (Vec::new(), Vec::new());
+
+    for block in data.split("\n\n").filter(|b| !b.trim().is_empty()) {
+        let rows: Vec<&[u8]> = block.lines().map(|l| l.trim().as_bytes()).collect();
+        let width = rows[0].len();
+        if rows.len() != PIN_SPACE as usize + 2 || rows.iter().any(|r| r.len() != width) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed schematic:\n{}", block),
+            ));
+        }
+
+        let heights: Vec<u8> = (0..width)
+            .map(|x| {
+                rows.iter()
+                    .filter(|r| r[x] == b'#')
+                    .count()
+                    .saturating_sub(1) as u8
+            })
+            .collect();
+
+        if rows[0].iter().all(|&c| c == b'#') {
+            locks.push(heights);
+        } else {
+            keys.push(heights);
+        }
+    }
+
+    Ok((locks, keys))
+}
+
+/// How many lock and key pairs fit together without overlapping.
+fn count_fits(locks: &[Heights], keys: &[Heights]) -> usize {
+    locks
+        .iter()
+        .flat_map(|lock| keys.iter().map(move |key| (lock, key)))
+        .filter(|(lock, key)| fits(lock, key))
+        .count()
+}
+
+fn fits(lock: &[u8], key: &[u8]) -> bool {
+    lock.iter().zip(key.iter()).all(|(l, k)| l + k <= PIN_SPACE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let (locks, keys) = parse_schematics(include_str!("../../day25/sample.txt")).unwrap();
+        assert_eq!(count_fits(&locks, &keys), 3);
+    }
+}
@@ -13,9 +13,44 @@ pub mod day12;
 pub mod day13;
 pub mod day14;
 pub mod day15;
-// pub mod day16;
+pub mod day16;
 pub mod day17;
+pub mod day18;
 pub mod day19;
+pub mod day20;
+pub mod day21;
 pub mod day22;
 pub mod day23;
 pub mod day24;
+pub mod day25;
+
+use aoc_core::runner::Solution;
+
+/// Every day the runner knows about, in order. `aoc new` adds to this list.
+pub const SOLUTIONS: &[Solution<crate::Options>] = &[
+    Solution::new(1, day01::INPUT, day01::run),
+    Solution::new(2, day02::INPUT, day02::run),
+    Solution::new(3, day03::INPUT, day03::run),
+    Solution::new(4, day04::INPUT, day04::run),
+    Solution::new(5, day05::INPUT, day05::run),
+    Solution::new(6, day06::INPUT, day06::run),
+    Solution::new(7, day07::INPUT, day07::run),
+    Solution::new(8, day08::INPUT, day08::run),
+    Solution::new(9, day09::INPUT, day09::run),
+    Solution::new(10, day10::INPUT, day10::run),
+    Solution::new(11, day11::INPUT, day11::run),
+    Solution::new(12, day12::INPUT, day12::run),
+    Solution::new(13, day13::INPUT, day13::run),
+    Solution::new(14, day14::INPUT, day14::run),
+    Solution::new(15, day15::INPUT, day15::run),
+    Solution::new(16, day16::INPUT, day16::run),
+    Solution::new(17, day17::INPUT, day17::run),
+    Solution::new(18, day18::INPUT, day18::run),
+    Solution::new(19, day19::INPUT, day19::run),
+    Solution::new(20, day20::INPUT, day20::run),
+    Solution::new(21, day21::INPUT, day21::run),
+    Solution::new(22, day22::INPUT, day22::run),
+    Solution::new(23, day23::INPUT, day23::run),
+    Solution::new(24, day24::INPUT, day24::run),
+    Solution::new(25, day25::INPUT, day25::run),
+];
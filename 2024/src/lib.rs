@@ -0,0 +1,60 @@
+pub mod days;
+
+use std::io;
+use std::path::Path;
+
+use aoc_core::runner::Year;
+use aoc_core::visualize;
+
+/// Command-line options for the 2024 days.
+#[derive(clap::Args, Debug, Clone)]
+#[group(skip)]
+pub struct Options {
+    #[clap(flatten)]
+    pub visualize: visualize::Options,
+
+    #[clap(flatten)]
+    pub day02: days::day02::Options,
+
+    #[clap(flatten)]
+    pub day03: days::day03::Options,
+
+    #[clap(flatten)]
+    pub day04: days::day04::Options,
+
+    #[clap(flatten)]
+    pub day07: days::day07::Options,
+
+    #[clap(flatten)]
+    pub day08: days::day08::Options,
+
+    #[clap(flatten)]
+    pub day09: days::day09::Options,
+
+    #[clap(flatten)]
+    pub day11: days::day11::Options,
+
+    #[clap(flatten)]
+    pub day12: days::day12::Options,
+
+    #[clap(flatten)]
+    pub day13: days::day13::Options,
+
+    #[clap(flatten)]
+    pub day14: days::day14::Options,
+
+    #[clap(flatten)]
+    pub day18: days::day18::Options,
+
+    #[clap(flatten)]
+    pub day20: days::day20::Options,
+
+    #[clap(flatten)]
+    pub day22: days::day22::Options,
+}
+
+/// The 2024 days, reading their inputs from `dir` or wherever
+/// [`Year::locate`] finds them.
+pub fn year(dir: Option<&Path>) -> io::Result<Year<Options>> {
+    Year::locate(2024, days::SOLUTIONS, env!("CARGO_MANIFEST_DIR"), dir)
+}
@@ -0,0 +1,206 @@
+//! Allocation counting for the `alloc-stats` feature. The binary installs
+//! [`Counting`] as the global allocator; the runner splits the counts by day
+//! and by part, where a part ends when the day reports its answer.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+static LIVE: AtomicU64 = AtomicU64::new(0);
+static PEAK: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting as it goes.
+pub struct Counting;
+
+fn allocated(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(size as u64, Ordering::Relaxed);
+    let live = LIVE.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+fn freed(size: usize) {
+    LIVE.fetch_sub(size as u64, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        freed(layout.size());
+    }
+
+    /// A reallocation counts as a new allocation of the new size.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            freed(layout.size());
+            allocated(new_size);
+        }
+        new
+    }
+}
+
+/// Allocations over some stretch of a run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub allocations: u64,
+    pub bytes: u64,
+    /// The most memory held at once, above what was live when it started.
+    pub peak: u64,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} allocated, {} peak",
+            self.allocations,
+            Bytes(self.bytes),
+            Bytes(self.peak)
+        )
+    }
+}
+
+/// A byte count in the largest unit that keeps it at least 1.
+pub struct Bytes(pub u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit + 1 < UNITS.len() {
+            value /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            write!(f, "{} B", self.0)
+        } else {
+            write!(f, "{:.1} {}", value, UNITS[unit])
+        }
+    }
+}
+
+/// The counters when a stretch started.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    allocations: u64,
+    bytes: u64,
+    live: u64,
+}
+
+impl Mark {
+    /// Starts a stretch, resetting the peak to what is live now.
+    fn now() -> Mark {
+        let live = LIVE.load(Ordering::Relaxed);
+        PEAK.store(live, Ordering::Relaxed);
+        Mark {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: BYTES.load(Ordering::Relaxed),
+            live,
+        }
+    }
+
+    /// The stretch since this mark, and the absolute peak within it.
+    fn since(&self) -> (Stats, u64) {
+        let peak = PEAK.load(Ordering::Relaxed);
+        let stats = Stats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed) - self.allocations,
+            bytes: BYTES.load(Ordering::Relaxed) - self.bytes,
+            peak: peak.saturating_sub(self.live),
+        };
+        (stats, peak)
+    }
+}
+
+/// A day's totals and its parts.
+#[derive(Debug, Default, Clone)]
+pub struct DayStats {
+    pub total: Stats,
+    pub parts: Vec<(u32, Stats)>,
+}
+
+#[derive(Debug)]
+struct Recording {
+    day: Mark,
+    part: Mark,
+    /// The highest absolute peak of any stretch so far.
+    peak: u64,
+    parts: Vec<(u32, Stats)>,
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+    static LAST: RefCell<Option<DayStats>> = const { RefCell::new(None) };
+}
+
+/// Starts counting for a day.
+pub fn begin_day() {
+    // Made before the mark so the bookkeeping isn't counted against the day.
+    let parts = Vec::with_capacity(4);
+    let mark = Mark::now();
+    RECORDING.with(|r| {
+        *r.borrow_mut() = Some(Recording {
+            day: mark,
+            part: mark,
+            peak: mark.live,
+            parts,
+        })
+    });
+}
+
+/// Closes the stretch for `part` and starts the next one.
+pub fn end_part(part: u32) {
+    let (stats, peak) = match RECORDING.with(|r| r.borrow().as_ref().map(|r| r.part)) {
+        Some(mark) => mark.since(),
+        None => return,
+    };
+    let next = Mark::now();
+    RECORDING.with(|r| {
+        if let Some(recording) = r.borrow_mut().as_mut() {
+            recording.peak = recording.peak.max(peak);
+            recording.parts.push((part, stats));
+            recording.part = next;
+        }
+    });
+}
+
+/// Stops counting for the day, keeping the result for [`last_day`].
+pub fn end_day() {
+    let Some(recording) = RECORDING.with(|r| r.borrow_mut().take()) else {
+        return;
+    };
+    let (mut total, peak) = recording.day.since();
+    total.peak = recording.peak.max(peak).saturating_sub(recording.day.live);
+    let stats = DayStats {
+        total,
+        parts: recording.parts,
+    };
+    LAST.with(|l| *l.borrow_mut() = Some(stats));
+}
+
+/// The counts for the last day run.
+pub fn last_day() -> Option<DayStats> {
+    LAST.with(|l| l.borrow().clone())
+}
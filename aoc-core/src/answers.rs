@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::guesses::{self, Bounds, GuessLog};
+
+/// Answers known to be right, one `part: answer` line per part. The site's
+/// confirmed answers live in `dayN/input.answers` and the hand-written sample
+/// answers in `dayN/sample.answers`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    parts: BTreeMap<u32, String>,
+}
+
+impl Answers {
+    /// Reads an answers file; a missing file has no answers.
+    pub fn load(path: &Path) -> io::Result<Answers> {
+        match fs::read_to_string(path) {
+            Ok(text) => Answers::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn parse(text: &str) -> io::Result<Answers> {
+        let mut parts = BTreeMap::new();
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parsed = line
+                .split_once(':')
+                .and_then(|(part, value)| Some((part.trim().parse().ok()?, value.trim())));
+            match parsed {
+                Some((part, value)) if !value.is_empty() => {
+                    parts.insert(part, value.to_string());
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: expected `part: answer`, got {:?}", n + 1, line),
+                    ))
+                }
+            }
+        }
+        Ok(Answers { parts })
+    }
+
+    pub fn get(&self, part: u32) -> Option<&str> {
+        self.parts.get(&part).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, part: u32, value: impl Into<String>) {
+        self.parts.insert(part, value.into());
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text: String = self
+            .parts
+            .iter()
+            .map(|(part, value)| format!("{}: {}\n", part, value))
+            .collect();
+        fs::write(path, text)
+    }
+}
+
+/// The confirmed answers for the puzzle input of `day`.
+pub fn input_answers_path(day: u32) -> PathBuf {
+    PathBuf::from(format!("day{}/input.answers", day))
+}
+
+/// Sample `k` for `day`: `dayN/sample.txt` for the first, then
+/// `dayN/sample2.txt` and so on.
+pub fn sample_path(day: u32, k: u32) -> PathBuf {
+    PathBuf::from(format!("day{}/{}", day, sample_file_name(k)))
+}
+
+/// The expected answers for sample `k`, next to the sample itself.
+pub fn sample_answers_path(day: u32, k: u32) -> PathBuf {
+    PathBuf::from(format!("day{}/{}.answers", day, sample_name(k)))
+}
+
+/// The file name of sample `k`, as in [`sample_path`].
+pub fn sample_file_name(k: u32) -> String {
+    format!("{}.txt", sample_name(k))
+}
+
+fn sample_name(k: u32) -> String {
+    match k {
+        1 => "sample".to_string(),
+        k => format!("sample{}", k),
+    }
+}
+
+/// Adds a confirmed answer to the answers file at `path`, e.g. a day's
+/// [`input_answers_path`].
+pub fn record(path: &Path, part: u32, value: &str) -> io::Result<()> {
+    let mut answers = Answers::load(path)?;
+    answers.insert(part, value);
+    answers.save(path)
+}
+
+/// Checks the answers a day reported against `dayN/input.answers`, or the
+/// sample's answers when it ran on a sample. A part without a confirmed
+/// input answer is checked against the guess log instead, which can only
+/// rule answers out. Returns whether nothing was wrong.
+pub fn verify(day: u32, sample: Option<u32>, reported: &[(u32, String)]) -> io::Result<bool> {
+    let (known, log) = match sample {
+        Some(k) => (
+            Answers::load(&sample_answers_path(day, k))?,
+            GuessLog::default(),
+        ),
+        None => (
+            Answers::load(&input_answers_path(day))?,
+            GuessLog::load(&guesses::path(day))?,
+        ),
+    };
+    let mut ok = true;
+
+    for (part, value) in reported {
+        let status = match known.get(*part) {
+            Some(correct) if correct == value => "ok".to_string(),
+            Some(correct) => {
+                ok = false;
+                format!("MISMATCH, expected {}", correct)
+            }
+            None => match log.check(*part, value) {
+                Err(rejection) => {
+                    ok = false;
+                    format!("WRONG, {}", rejection)
+                }
+                Ok(()) => match log.bounds(*part) {
+                    bounds if bounds == Bounds::default() => "unconfirmed".to_string(),
+                    bounds => format!("unconfirmed, the answer is {}", bounds),
+                },
+            },
+        };
+        println!("Day {} Part {}: {} {}", day, part, value, status);
+    }
+
+    Ok(ok)
+}
@@ -0,0 +1,436 @@
+//! Repeated runs of a day, for timings steady enough to compare between
+//! builds. A single run is at the mercy of whatever else the machine is
+//! doing, so days are run several times and summarised by the median.
+//! Results can be saved as named snapshots in the year's `bench/` directory
+//! and compared later.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "alloc-stats")]
+use crate::alloc_stats;
+use crate::runner::{self, Solution, Year};
+
+/// Timed runs per day unless asked for more or fewer.
+pub const DEFAULT_RUNS: usize = 10;
+
+/// Timings over several runs.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub runs: usize,
+    #[serde(with = "nanos")]
+    pub median: Duration,
+    #[serde(with = "nanos")]
+    pub min: Duration,
+    #[serde(with = "nanos")]
+    pub max: Duration,
+    /// The median distance of a run from the median: how noisy the runs
+    /// were, without letting one slow outlier dominate.
+    #[serde(with = "nanos", default)]
+    pub mad: Duration,
+    /// The same for each part the day reported an answer for.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parts: BTreeMap<u32, Measurement>,
+    /// What the last run allocated, with the `alloc-stats` feature.
+    #[cfg(feature = "alloc-stats")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocations: Option<alloc_stats::Stats>,
+}
+
+impl Measurement {
+    fn from_times(mut times: Vec<Duration>) -> Measurement {
+        times.sort_unstable();
+        let middle = median(&times);
+        let mut deviations: Vec<Duration> = times.iter().map(|&t| t.abs_diff(middle)).collect();
+        deviations.sort_unstable();
+        Measurement {
+            runs: times.len(),
+            median: middle,
+            min: times[0],
+            max: times[times.len() - 1],
+            mad: median(&deviations),
+            parts: BTreeMap::new(),
+            #[cfg(feature = "alloc-stats")]
+            allocations: None,
+        }
+    }
+}
+
+/// The middle of some sorted, non-empty times.
+fn median(sorted: &[Duration]) -> Duration {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Measurements for some of a year's days, as saved to JSON.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub year: u32,
+    /// When it was saved, in milliseconds since the Unix epoch.
+    #[serde(default)]
+    pub saved: u64,
+    pub days: BTreeMap<u32, Measurement>,
+}
+
+impl Snapshot {
+    /// Reads a snapshot; a missing file is `None`.
+    pub fn load(path: &Path) -> io::Result<Option<Snapshot>> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes the snapshot, stamped with the current time.
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        self.saved = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+}
+
+const HISTORY_DIR: &str = "bench";
+
+/// Where the snapshot called `name` is kept.
+pub fn snapshot_path(name: &str) -> io::Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} can't be used as a snapshot name", name),
+        ));
+    }
+    Ok(Path::new(HISTORY_DIR).join(format!("{}.json", name)))
+}
+
+/// Reads the snapshot called `name`, which must exist.
+pub fn load_named(name: &str) -> io::Result<Snapshot> {
+    Snapshot::load(&snapshot_path(name)?)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("there is no snapshot called {:?}", name),
+        )
+    })
+}
+
+/// Every saved snapshot with its name, oldest first.
+pub fn history() -> io::Result<Vec<(String, Snapshot)>> {
+    let entries = match fs::read_dir(HISTORY_DIR) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if path.extension().is_some_and(|e| e == "json") {
+            if let Some(snapshot) = Snapshot::load(&path)? {
+                snapshots.push((name.to_string(), snapshot));
+            }
+        }
+    }
+    snapshots.sort_by(|(a, x), (b, y)| (x.saved, a).cmp(&(y.saved, b)));
+    Ok(snapshots)
+}
+
+/// Runs a day once to warm up, then `runs` more times with the clock going.
+pub fn measure<O>(
+    solution: &Solution<O>,
+    options: &O,
+    runs: usize,
+) -> Result<Measurement, Box<dyn std::error::Error>> {
+    let runs = runs.max(1);
+    runner::run_quietly(solution, options)?;
+
+    let mut times = Vec::with_capacity(runs);
+    let mut part_times: BTreeMap<u32, Vec<Duration>> = BTreeMap::new();
+    for _ in 0..runs {
+        let start = Instant::now();
+        let parts = runner::run_quietly(solution, options)?;
+        times.push(start.elapsed());
+
+        // A part reported more than once took all of its stretches.
+        let mut totals: BTreeMap<u32, Duration> = BTreeMap::new();
+        for (part, time) in parts {
+            *totals.entry(part).or_default() += time;
+        }
+        for (part, time) in totals {
+            part_times.entry(part).or_default().push(time);
+        }
+    }
+
+    let mut measurement = Measurement::from_times(times);
+    measurement.parts = part_times
+        .into_iter()
+        .map(|(part, times)| (part, Measurement::from_times(times)))
+        .collect();
+
+    // Allocations barely vary between runs, so the last one stands for all.
+    #[cfg(feature = "alloc-stats")]
+    if let Some(stats) = alloc_stats::last_day() {
+        measurement.allocations = Some(stats.total);
+        for (part, stats) in stats.parts {
+            if let Some(m) = measurement.parts.get_mut(&part) {
+                let total = m.allocations.get_or_insert_default();
+                total.allocations += stats.allocations;
+                total.bytes += stats.bytes;
+                total.peak = total.peak.max(stats.peak);
+            }
+        }
+    }
+    Ok(measurement)
+}
+
+/// Measures each of `days` in turn, on normalised input. Days that fail are
+/// reported and left out rather than stopping the rest.
+pub fn measure_days<O>(
+    year: &Year<O>,
+    options: &O,
+    days: &[u32],
+    runs: usize,
+) -> io::Result<Snapshot> {
+    let mut snapshot = Snapshot {
+        year: year.year,
+        ..Snapshot::default()
+    };
+    for &day in days {
+        let Some(solution) = year.find(day) else {
+            println!("Solution for day {} is not implemented yet.", day);
+            continue;
+        };
+        if let Err(e) = year.enter_day(day, None) {
+            println!("Could not read the input for day {}: {}", day, e);
+            continue;
+        }
+        match measure(solution, options, runs) {
+            Ok(measurement) => {
+                snapshot.days.insert(day, measurement);
+            }
+            Err(e) => println!("Error in day {}: {:?}", day, e),
+        }
+    }
+    Ok(snapshot)
+}
+
+/// The change from `before` to `after`, in percent.
+pub fn change(before: Duration, after: Duration) -> f64 {
+    (after.as_secs_f64() / before.as_secs_f64() - 1.0) * 100.0
+}
+
+/// Whether the medians moved by more than the runs' noise could explain.
+/// The MADs are scaled to standard deviations (× 1.4826, for normal noise)
+/// and combined, and the change has to be over three of them.
+pub fn significant(before: &Measurement, after: &Measurement) -> bool {
+    let noise = before.mad.as_secs_f64().hypot(after.mad.as_secs_f64()) * 1.4826;
+    after.median.abs_diff(before.median).as_secs_f64() > 3.0 * noise
+}
+
+/// Headings for [`alloc_columns`], empty without the `alloc-stats` feature.
+pub fn alloc_headings() -> String {
+    if cfg!(feature = "alloc-stats") {
+        format!(" {:>10} {:>10} {:>10}", "allocs", "bytes", "peak")
+    } else {
+        String::new()
+    }
+}
+
+/// The allocation count, bytes allocated and peak memory of a measurement,
+/// to follow its timings in a table. Empty without the `alloc-stats` feature.
+#[cfg(feature = "alloc-stats")]
+pub fn alloc_columns(measurement: &Measurement) -> String {
+    match &measurement.allocations {
+        Some(stats) => format!(
+            " {:>10} {:>10} {:>10}",
+            stats.allocations,
+            alloc_stats::Bytes(stats.bytes).to_string(),
+            alloc_stats::Bytes(stats.peak).to_string()
+        ),
+        None => format!(" {:>10} {:>10} {:>10}", "-", "-", "-"),
+    }
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+pub fn alloc_columns(_measurement: &Measurement) -> String {
+    String::new()
+}
+
+/// One row per day, then one per part, with its spread and, with the
+/// `alloc-stats` feature, its allocations.
+pub fn print_table(snapshot: &Snapshot) {
+    println!(
+        "  {:<5} {:<5} {:>12} {:>12} {:>12} {:>12}{}",
+        "day",
+        "part",
+        "median",
+        "min",
+        "max",
+        "noise",
+        alloc_headings()
+    );
+    for (day, measurement) in &snapshot.days {
+        let rows = std::iter::once((String::new(), measurement)).chain(
+            measurement
+                .parts
+                .iter()
+                .map(|(part, m)| (part.to_string(), m)),
+        );
+        for (part, m) in rows {
+            println!(
+                "  {:<5} {:<5} {:>12} {:>12} {:>12} {:>12}{}",
+                day,
+                part,
+                format!("{:.2?}", m.median),
+                format!("{:.2?}", m.min),
+                format!("{:.2?}", m.max),
+                format!("±{:.2?}", m.mad),
+                alloc_columns(m)
+            );
+        }
+    }
+}
+
+/// The medians of `before` and `after` side by side for each day and part,
+/// marking the changes that stand out from the noise.
+pub fn print_comparison(before: &Snapshot, after: &Snapshot) {
+    println!(
+        "  {:<5} {:<5} {:>12} {:>12} {:>9}",
+        "day", "part", "before", "after", "change"
+    );
+    let days: BTreeSet<u32> = before
+        .days
+        .keys()
+        .chain(after.days.keys())
+        .copied()
+        .collect();
+    for day in days {
+        let (b, a) = (before.days.get(&day), after.days.get(&day));
+        print_change(day, "", b, a);
+
+        let parts: BTreeSet<u32> = b
+            .into_iter()
+            .chain(a)
+            .flat_map(|m| m.parts.keys().copied())
+            .collect();
+        for part in parts {
+            let b = b.and_then(|m| m.parts.get(&part));
+            let a = a.and_then(|m| m.parts.get(&part));
+            print_change(day, &part.to_string(), b, a);
+        }
+    }
+    println!("  (faster and slower are beyond three times the combined noise of the runs)");
+}
+
+fn print_change(day: u32, part: &str, before: Option<&Measurement>, after: Option<&Measurement>) {
+    let show = |m: Option<&Measurement>| m.map_or("-".to_string(), |m| format!("{:.2?}", m.median));
+    let (change, marker) = match (before, after) {
+        (Some(b), Some(a)) => (
+            format!("{:+.1}%", change(b.median, a.median)),
+            match (significant(b, a), a.median < b.median) {
+                (false, _) => "~",
+                (true, true) => "faster",
+                (true, false) => "slower",
+            },
+        ),
+        _ => ("-".to_string(), ""),
+    };
+    println!(
+        "  {:<5} {:<5} {:>12} {:>12} {:>9}  {}",
+        day,
+        part,
+        show(before),
+        show(after),
+        change,
+        marker
+    );
+}
+
+/// A bar per value, from lowest to highest; a gap where there is none.
+pub fn sparkline(values: &[Option<Duration>]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let present = values.iter().flatten();
+    let (Some(low), Some(high)) = (present.clone().min(), present.max()) else {
+        return " ".repeat(values.len());
+    };
+    let range = (*high - *low).as_secs_f64();
+    values
+        .iter()
+        .map(|v| match v {
+            None => ' ',
+            Some(_) if range == 0.0 => BARS[0],
+            Some(v) => {
+                let level = (*v - *low).as_secs_f64() / range * (BARS.len() - 1) as f64;
+                BARS[level.round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// A sparkline of each day's median across every saved snapshot, or just
+/// `day`'s, with its first and latest value.
+pub fn print_history(day: Option<u32>) -> io::Result<()> {
+    let history = history()?;
+    if history.is_empty() {
+        println!("No snapshots saved yet; save one with `aoc bench --save <name>`");
+        return Ok(());
+    }
+    let names: Vec<&str> = history.iter().map(|(name, _)| name.as_str()).collect();
+    println!(
+        "{} snapshots, oldest first: {}",
+        names.len(),
+        names.join(", ")
+    );
+
+    let days: BTreeSet<u32> = history
+        .iter()
+        .flat_map(|(_, s)| s.days.keys().copied())
+        .filter(|&d| day.is_none_or(|day| day == d))
+        .collect();
+    for d in days {
+        let medians: Vec<Option<Duration>> = history
+            .iter()
+            .map(|(_, s)| s.days.get(&d).map(|m| m.median))
+            .collect();
+        let mut present = medians.iter().flatten();
+        let first = present.next().copied().unwrap_or_default();
+        let latest = present.last().copied().unwrap_or(first);
+        println!(
+            "  {:<5} {}  {:.2?} -> {:.2?}",
+            d,
+            sparkline(&medians),
+            first,
+            latest
+        );
+    }
+    Ok(())
+}
+
+/// Durations go to JSON as whole nanoseconds.
+mod nanos {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(d.as_nanos() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_nanos(u64::deserialize(d)?))
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::bench::{self, Snapshot};
+use crate::runner::Year;
+
+/// The most each day may take, one `day05 = "5ms"` line per day in the
+/// year's `budgets.toml`. Days without a line have no budget.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Budgets {
+    days: BTreeMap<u32, Duration>,
+}
+
+impl Budgets {
+    /// Reads a budgets file; a missing file has no budgets.
+    pub fn load(path: &Path) -> io::Result<Budgets> {
+        match fs::read_to_string(path) {
+            Ok(text) => Budgets::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Budgets::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn parse(text: &str) -> io::Result<Budgets> {
+        let mut days = BTreeMap::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = line.split_once('=').and_then(|(key, value)| {
+                let day = key.trim().strip_prefix("day")?.parse().ok()?;
+                let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+                Some((day, parse_duration(value)?))
+            });
+            match parsed {
+                Some((day, budget)) => {
+                    days.insert(day, budget);
+                }
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: expected `dayNN = \"5ms\"`, got {:?}", n + 1, line),
+                    ))
+                }
+            }
+        }
+        Ok(Budgets { days })
+    }
+
+    pub fn get(&self, day: u32) -> Option<Duration> {
+        self.days.get(&day).copied()
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = u32> + '_ {
+        self.days.keys().copied()
+    }
+}
+
+pub fn budgets_path() -> PathBuf {
+    PathBuf::from("budgets.toml")
+}
+
+/// The timings `--check-budget` compares against. Kept next to the budgets
+/// rather than in `bench/`, so it is neither one of the snapshots `aoc bench`
+/// lists nor one it can overwrite.
+pub fn baseline_path() -> PathBuf {
+    PathBuf::from("budgets.baseline.json")
+}
+
+/// A duration like `5ms`, `1.5s`, `800us` or `800µs`.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let split = text.find(|c: char| c.is_alphabetic())?;
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.trim().parse().ok()?;
+    let seconds = match unit {
+        "s" => number,
+        "ms" => number / 1e3,
+        "us" | "µs" => number / 1e6,
+        "ns" => number / 1e9,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// How `--check-budget` runs.
+#[derive(Debug, Clone, Copy)]
+pub struct Check {
+    /// Timed runs per day.
+    pub runs: usize,
+    /// How far over budget, in percent, a day may go before it fails.
+    pub tolerance: f64,
+    /// Only check this day.
+    pub day: Option<u32>,
+    /// Keep this run's timings as the baseline for the next check.
+    pub save_baseline: bool,
+}
+
+/// Benchmarks the days with budgets and reports each against its budget
+/// and against the baseline. Returns false if any day went over its budget
+/// by more than the tolerance.
+pub fn check<O>(year: &Year<O>, options: &O, check: &Check) -> io::Result<bool> {
+    let budgets = Budgets::load(&budgets_path())?;
+    let days: Vec<u32> = budgets
+        .days()
+        .filter(|&day| check.day.is_none_or(|d| d == day))
+        .collect();
+    if days.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            match check.day {
+                Some(day) => format!("day {} has no budget in budgets.toml", day),
+                None => "there are no budgets in budgets.toml".to_string(),
+            },
+        ));
+    }
+
+    println!(
+        "Checking {} days against their budgets, median of {} runs, {}% tolerance",
+        days.len(),
+        check.runs.max(1),
+        check.tolerance
+    );
+    let snapshot = bench::measure_days(year, options, &days, check.runs)?;
+    let baseline = Snapshot::load(&baseline_path())?;
+
+    println!(
+        "  {:<5} {:>12} {:>12} {:>10}  {:<6} {:>12} {:>10}{}",
+        "day",
+        "median",
+        "budget",
+        "vs budget",
+        "",
+        "baseline",
+        "change",
+        bench::alloc_headings()
+    );
+    let mut over = 0;
+    for (&day, measurement) in &snapshot.days {
+        let Some(budget) = budgets.get(day) else {
+            continue;
+        };
+        let vs_budget = bench::change(budget, measurement.median);
+        let status = if measurement.median <= budget {
+            "ok"
+        } else if vs_budget <= check.tolerance {
+            "close"
+        } else {
+            over += 1;
+            "OVER"
+        };
+        let (before, change) = match baseline.as_ref().and_then(|b| b.days.get(&day)) {
+            Some(before) => (
+                format!("{:.2?}", before.median),
+                format!("{:+.1}%", bench::change(before.median, measurement.median)),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        println!(
+            "  {:<5} {:>12} {:>12} {:>10}  {:<6} {:>12} {:>10}{}",
+            day,
+            format!("{:.2?}", measurement.median),
+            format!("{:?}", budget),
+            format!("{:+.1}%", vs_budget),
+            status,
+            before,
+            change,
+            bench::alloc_columns(measurement)
+        );
+    }
+
+    let failed = days.len() - snapshot.days.len();
+    match (over, failed) {
+        (0, 0) => println!("All days within budget"),
+        (0, _) => println!("{} of {} days could not be run", failed, days.len()),
+        _ => println!("{} of {} days over budget", over, days.len()),
+    }
+
+    if check.save_baseline {
+        let path = baseline_path();
+        let mut saved = baseline.unwrap_or_default();
+        saved.year = snapshot.year;
+        saved.days.extend(snapshot.days);
+        saved.save(&path)?;
+        println!("Saved the baseline to {}", path.display());
+    }
+
+    Ok(over == 0 && failed == 0)
+}
@@ -0,0 +1,267 @@
+/// A square matrix over GF(2) of size at most 64, one `u64` bitmask per row.
+/// Addition is XOR and multiplication is AND, so applying the matrix to a
+/// vector is a parity count per row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    n: usize,
+    rows: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn identity(n: usize) -> BitMatrix {
+        assert!(n <= 64, "BitMatrix supports at most 64 bits");
+        BitMatrix {
+            n,
+            rows: (0..n).map(|i| 1 << i).collect(),
+        }
+    }
+
+    /// Builds the matrix of a linear map from its images of the basis vectors:
+    /// `columns[j]` is where bit `j` is sent.
+    pub fn from_columns(columns: &[u64]) -> BitMatrix {
+        let n = columns.len();
+        assert!(n <= 64, "BitMatrix supports at most 64 bits");
+        let mut rows = vec![0u64; n];
+        for (j, &col) in columns.iter().enumerate() {
+            for (i, row) in rows.iter_mut().enumerate() {
+                *row |= ((col >> i) & 1) << j;
+            }
+        }
+        BitMatrix { n, rows }
+    }
+
+    /// Recovers the matrix of a linear map on `n`-bit values by probing it
+    /// with each basis vector.
+    pub fn from_linear_map(n: usize, f: impl Fn(u64) -> u64) -> BitMatrix {
+        let columns: Vec<u64> = (0..n).map(|j| f(1 << j)).collect();
+        BitMatrix::from_columns(&columns)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == BitMatrix::identity(self.n)
+    }
+
+    pub fn apply(&self, v: u64) -> u64 {
+        self.rows.iter().enumerate().fold(0, |acc, (i, &row)| {
+            acc | (((row & v).count_ones() as u64) & 1) << i
+        })
+    }
+
+    pub fn mul(&self, other: &BitMatrix) -> BitMatrix {
+        assert_eq!(self.n, other.n);
+        let rows = self
+            .rows
+            .iter()
+            .map(|&row| {
+                (0..self.n)
+                    .filter(|&k| (row >> k) & 1 == 1)
+                    .fold(0, |acc, k| acc ^ other.rows[k])
+            })
+            .collect();
+        BitMatrix { n: self.n, rows }
+    }
+
+    /// `self^e` by repeated squaring.
+    pub fn pow(&self, mut e: u64) -> BitMatrix {
+        let mut result = BitMatrix::identity(self.n);
+        let mut base = self.clone();
+        while e > 0 {
+            if e & 1 == 1 {
+                result = result.mul(&base);
+            }
+            base = base.mul(&base);
+            e >>= 1;
+        }
+        result
+    }
+
+    /// Gauss-Jordan elimination against the identity; `None` if singular.
+    pub fn inverse(&self) -> Option<BitMatrix> {
+        let mut a = self.rows.clone();
+        let mut inv = BitMatrix::identity(self.n).rows;
+
+        for col in 0..self.n {
+            let pivot = (col..self.n).find(|&r| (a[r] >> col) & 1 == 1)?;
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+            for r in 0..self.n {
+                if r != col && (a[r] >> col) & 1 == 1 {
+                    a[r] ^= a[col];
+                    inv[r] ^= inv[col];
+                }
+            }
+        }
+
+        Some(BitMatrix {
+            n: self.n,
+            rows: inv,
+        })
+    }
+
+    /// The smallest `k > 0` with `self^k == I`, given some multiple `m` of it
+    /// (for example the group order). Divides out each prime factor of `m` for
+    /// as long as the power stays the identity.
+    pub fn order_dividing(&self, m: u64) -> Option<u64> {
+        if !self.pow(m).is_identity() {
+            return None;
+        }
+        Some(reduce_order(m, |k| self.pow(k).is_identity()))
+    }
+
+    /// The smallest `k > 0` with `self^k v == v`, given a multiple `m` of it.
+    pub fn period_dividing(&self, v: u64, m: u64) -> Option<u64> {
+        if self.pow(m).apply(v) != v {
+            return None;
+        }
+        Some(reduce_order(m, |k| self.pow(k).apply(v) == v))
+    }
+
+    /// The powers the period of every vector can be read from, given a
+    /// multiple `m` of the matrix's order; `None` if `m` isn't one. For many
+    /// vectors this is much cheaper than [`BitMatrix::period_dividing`] on
+    /// each, which raises the matrix to new powers every time.
+    pub fn periods(&self, m: u64) -> Option<Periods> {
+        if !self.pow(m).is_identity() {
+            return None;
+        }
+        let primes = prime_factors(m)
+            .into_iter()
+            .map(|p| {
+                let mut powers = Vec::new();
+                let mut k = m;
+                while k.is_multiple_of(p) {
+                    k /= p;
+                    powers.push(self.pow(k));
+                }
+                (p, powers)
+            })
+            .collect();
+        Some(Periods { m, primes })
+    }
+}
+
+/// Periods of vectors under one matrix `A`, from [`BitMatrix::periods`].
+/// A vector's period divides `m`, and `A^(m / p^j) v == v` exactly when
+/// `p^j` is no more than the share of `p` in `m` the period doesn't need, so
+/// trying `j = 1, 2, ...` for each prime finds it.
+pub struct Periods {
+    m: u64,
+    /// Each prime factor `p` of `m`, with `A^(m / p^j)` for `j = 1, 2, ...`
+    /// while `p^j` divides `m`.
+    primes: Vec<(u64, Vec<BitMatrix>)>,
+}
+
+impl Periods {
+    /// The smallest `k > 0` with `A^k v == v`.
+    pub fn of(&self, v: u64) -> u64 {
+        let mut period = self.m;
+        for (p, powers) in &self.primes {
+            for power in powers {
+                if power.apply(v) != v {
+                    break;
+                }
+                period /= p;
+            }
+        }
+        period
+    }
+}
+
+/// Shrinks `m`, known to satisfy `holds`, to the smallest divisor that still
+/// does. Correct whenever the set of `k` satisfying `holds` is the multiples of
+/// some order, as it is for matrix and vector periods.
+fn reduce_order(mut m: u64, holds: impl Fn(u64) -> bool) -> u64 {
+    for p in prime_factors(m) {
+        while m.is_multiple_of(p) && holds(m / p) {
+            m /= p;
+        }
+    }
+    m
+}
+
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rotates 5-bit values left by one, which has order 5.
+    fn rotate(v: u64) -> u64 {
+        ((v << 1) | (v >> 4)) & 0x1F
+    }
+
+    /// Swaps bits 0 and 1 and cycles bits 2, 3 and 4, which has order 6.
+    fn swap_and_cycle(v: u64) -> u64 {
+        let swapped = ((v & 1) << 1) | ((v >> 1) & 1);
+        let cycled = ((v << 1) & 0b11000) | ((v >> 2) & 0b100);
+        swapped | cycled
+    }
+
+    /// A 24-bit xorshift, the kind of map these matrices are built from.
+    fn xorshift(v: u64) -> u64 {
+        let a = (v ^ (v << 6)) & 0xFFFFFF;
+        let b = a ^ (a >> 5);
+        (b ^ (b << 11)) & 0xFFFFFF
+    }
+
+    #[test]
+    fn inverse_undoes_the_matrix() {
+        let m = BitMatrix::from_linear_map(24, xorshift);
+        let inverse = m.inverse().unwrap();
+        assert!(inverse.mul(&m).is_identity());
+        assert!(m.mul(&inverse).is_identity());
+
+        let singular = BitMatrix::from_columns(&[0b01, 0b01]);
+        assert_eq!(singular.inverse(), None);
+    }
+
+    #[test]
+    fn pow_matches_repeated_steps() {
+        let m = BitMatrix::from_linear_map(24, xorshift);
+        let v = 123;
+        let mut stepped = v;
+        for n in 0..=100 {
+            assert_eq!(m.pow(n).apply(v), stepped, "after {} steps", n);
+            stepped = xorshift(stepped);
+        }
+    }
+
+    #[test]
+    fn orders_and_periods() {
+        let rotation = BitMatrix::from_linear_map(5, rotate);
+        assert_eq!(rotation.order_dividing(5), Some(5));
+        assert_eq!(rotation.order_dividing(10), Some(5));
+        assert_eq!(rotation.order_dividing(4), None);
+
+        let m = BitMatrix::from_linear_map(5, swap_and_cycle);
+        assert_eq!(m.order_dividing(12), Some(6));
+        let periods = m.periods(12).unwrap();
+        for (v, period) in [
+            (0, 1),
+            (0b00011, 1),
+            (0b00001, 2),
+            (0b00100, 3),
+            (0b00101, 6),
+        ] {
+            assert_eq!(periods.of(v), period, "{:05b}", v);
+            assert_eq!(m.period_dividing(v, 12), Some(period), "{:05b}", v);
+        }
+        assert!(m.periods(4).is_none());
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+use std::ops::{Index, IndexMut};
+
+/// A rectangular grid stored row-major, indexed by `(x, y)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell at a signed position, `None` off the edge.
+    pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some(&self.cells[y as usize * self.width + x as usize])
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+}
+
+impl Grid<char> {
+    /// One row per non-empty line; every row must be the same width.
+    pub fn parse(text: &str) -> Result<Grid<char>, io::Error> {
+        let rows: Vec<Vec<char>> = text
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.chars().collect())
+            .collect();
+        let width = rows.first().map_or(0, |r| r.len());
+        if let Some(y) = rows.iter().position(|r| r.len() != width) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("grid row {} is not {} wide", y + 1, width),
+            ));
+        }
+
+        Ok(Grid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        &mut self.cells[y * self.width + x]
+    }
+}
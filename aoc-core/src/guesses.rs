@@ -0,0 +1,199 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::site::Verdict;
+
+/// What the site said about one guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+}
+
+impl Outcome {
+    /// The outcome worth logging, if the site checked the answer at all.
+    pub fn from_verdict(verdict: &Verdict) -> Option<Outcome> {
+        match verdict {
+            Verdict::Correct => Some(Outcome::Correct),
+            Verdict::TooHigh => Some(Outcome::TooHigh),
+            Verdict::TooLow => Some(Outcome::TooLow),
+            Verdict::Wrong => Some(Outcome::Wrong),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Correct => "correct",
+            Outcome::TooHigh => "too-high",
+            Outcome::TooLow => "too-low",
+            Outcome::Wrong => "wrong",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Outcome> {
+        [
+            Outcome::Correct,
+            Outcome::TooHigh,
+            Outcome::TooLow,
+            Outcome::Wrong,
+        ]
+        .into_iter()
+        .find(|o| o.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Guess {
+    pub part: u32,
+    pub answer: String,
+    pub outcome: Outcome,
+}
+
+/// Every answer submitted for a day, kept in `dayN/guesses.txt` as
+/// `part: answer outcome` lines, oldest first.
+#[derive(Debug, Default, Clone)]
+pub struct GuessLog {
+    guesses: Vec<Guess>,
+}
+
+/// The open interval a numeric answer must lie in, from the too-low and
+/// too-high guesses so far.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub above: Option<i128>,
+    pub below: Option<i128>,
+}
+
+impl Bounds {
+    pub fn contains(&self, value: i128) -> bool {
+        self.above.is_none_or(|low| value > low) && self.below.is_none_or(|high| value < high)
+    }
+}
+
+impl fmt::Display for Bounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.above, self.below) {
+            (Some(low), Some(high)) => write!(f, "between {} and {}", low, high),
+            (Some(low), None) => write!(f, "above {}", low),
+            (None, Some(high)) => write!(f, "below {}", high),
+            (None, None) => write!(f, "unbounded"),
+        }
+    }
+}
+
+/// Why an answer can't be right, going by earlier guesses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    AlreadyWrong(Outcome),
+    OutOfBounds(Bounds),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::AlreadyWrong(outcome) => {
+                write!(f, "it was already guessed ({})", outcome.name())
+            }
+            Rejection::OutOfBounds(bounds) => write!(f, "the answer is {}", bounds),
+        }
+    }
+}
+
+/// Where the guesses for `day` are logged.
+pub fn path(day: u32) -> PathBuf {
+    PathBuf::from(format!("day{}/guesses.txt", day))
+}
+
+impl GuessLog {
+    /// The log at `path`, e.g. [`path`] for a day; no file means no guesses
+    /// yet.
+    pub fn load(path: &Path) -> io::Result<GuessLog> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(GuessLog::default()),
+            Err(e) => return Err(e),
+        };
+
+        let mut guesses = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let guess = line.split_once(':').and_then(|(part, rest)| {
+                let (answer, outcome) = rest.trim().rsplit_once(' ')?;
+                Some(Guess {
+                    part: part.trim().parse().ok()?,
+                    answer: answer.trim().to_string(),
+                    outcome: Outcome::from_name(outcome)?,
+                })
+            });
+            match guess {
+                Some(guess) => guesses.push(guess),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "{} line {}: expected `part: answer outcome`, got {:?}",
+                            path.display(),
+                            n + 1,
+                            line
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(GuessLog { guesses })
+    }
+
+    /// Appends a guess to the log file at `path`.
+    pub fn append(path: &Path, guess: &Guess) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(
+            file,
+            "{}: {} {}",
+            guess.part,
+            guess.answer,
+            guess.outcome.name()
+        )
+    }
+
+    pub fn bounds(&self, part: u32) -> Bounds {
+        let mut bounds = Bounds::default();
+        for guess in self.guesses.iter().filter(|g| g.part == part) {
+            let Ok(value) = guess.answer.parse::<i128>() else {
+                continue;
+            };
+            match guess.outcome {
+                Outcome::TooLow => bounds.above = bounds.above.max(Some(value)),
+                Outcome::TooHigh => {
+                    bounds.below = Some(bounds.below.map_or(value, |b| b.min(value)))
+                }
+                Outcome::Correct | Outcome::Wrong => {}
+            }
+        }
+        bounds
+    }
+
+    /// Checks `answer` against the earlier guesses for `part`: it mustn't
+    /// repeat a wrong one, and if it is a number it must be inside the bounds.
+    pub fn check(&self, part: u32, answer: &str) -> Result<(), Rejection> {
+        let repeated = self
+            .guesses
+            .iter()
+            .find(|g| g.part == part && g.answer == answer && g.outcome != Outcome::Correct);
+        if let Some(guess) = repeated {
+            return Err(Rejection::AlreadyWrong(guess.outcome));
+        }
+
+        let bounds = self.bounds(part);
+        match answer.parse::<i128>() {
+            Ok(value) if !bounds.contains(value) => Err(Rejection::OutOfBounds(bounds)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// An 8-bit grayscale image, stored row by row.
+#[derive(Debug, Clone)]
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    pub fn new(width: usize, height: usize) -> Self {
+        GrayImage {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.pixels.chunks(self.width.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImageFormat {
+    /// Plain-text bitmap, any non-zero pixel is drawn black
+    Pbm,
+    /// Plain-text graymap
+    Pgm,
+    /// 8-bit grayscale PNG
+    Png,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Pbm => "pbm",
+            ImageFormat::Pgm => "pgm",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+pub fn save(image: &GrayImage, format: ImageFormat, path: &Path) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    match format {
+        ImageFormat::Pbm => write_pbm(&mut w, image)?,
+        ImageFormat::Pgm => write_pgm(&mut w, image)?,
+        ImageFormat::Png => write_png(&mut w, image)?,
+    }
+    w.flush()
+}
+
+pub fn write_pbm<W: Write>(w: &mut W, image: &GrayImage) -> io::Result<()> {
+    writeln!(w, "P1")?;
+    writeln!(w, "{} {}", image.width, image.height)?;
+    for row in image.rows() {
+        let line: Vec<&str> = row.iter().map(|&p| if p > 0 { "1" } else { "0" }).collect();
+        writeln!(w, "{}", line.join(" "))?;
+    }
+    Ok(())
+}
+
+pub fn write_pgm<W: Write>(w: &mut W, image: &GrayImage) -> io::Result<()> {
+    writeln!(w, "P2")?;
+    writeln!(w, "{} {}", image.width, image.height)?;
+    writeln!(w, "255")?;
+    for row in image.rows() {
+        let line: Vec<String> = row.iter().map(|p| p.to_string()).collect();
+        writeln!(w, "{}", line.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Writes a PNG whose zlib stream only uses stored (uncompressed) deflate
+/// blocks. The files are larger than they need to be, but any viewer can open
+/// them and it saves pulling in a compression crate.
+pub fn write_png<W: Write>(w: &mut W, image: &GrayImage) -> io::Result<()> {
+    w.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // bit depth 8, colour type 0 (grayscale), default compression/filter, no interlace
+    header.extend_from_slice(&[8, 0, 0, 0, 0]);
+    write_chunk(w, b"IHDR", &header)?;
+
+    // Every scanline starts with its filter type, 0 == none.
+    let mut raw = Vec::with_capacity((image.width + 1) * image.height);
+    for row in image.rows() {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    write_chunk(w, b"IDAT", &zlib_stored(&raw))?;
+    write_chunk(w, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()).copied());
+    w.write_all(&crc.to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+
+    // CMF/FLG for deflate with a 32K window and no preset dictionary.
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(u8::from(last));
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(bytes: impl Iterator<Item = u8>) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
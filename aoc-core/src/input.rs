@@ -0,0 +1,501 @@
+//! Puzzle inputs as the days expect them. Inputs saved by hand or on
+//! another machine can differ from what the site serves in ways that break
+//! a day without being wrong: CRLF line endings, extra blank lines at the
+//! end or between sections, whitespace on blank lines. [`normalise`] undoes
+//! those as days [`read`] their inputs, and a day's [`Shape`] says what its
+//! input should look like so `aoc check-input` can point at the line that
+//! doesn't.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::answers;
+use crate::runner;
+
+/// An input brought into the form the site serves, with what changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalised {
+    /// `\n` line endings, empty blank lines, a single blank line between
+    /// sections, and exactly one newline at the end.
+    pub text: String,
+    /// The line number in the original of each line of `text`.
+    pub line_numbers: Vec<usize>,
+    /// What was changed, one note each.
+    pub fixes: Vec<String>,
+}
+
+impl Normalised {
+    /// The lines with their original line numbers.
+    fn lines(&self) -> Vec<(usize, &str)> {
+        self.line_numbers
+            .iter()
+            .copied()
+            .zip(self.text.lines())
+            .collect()
+    }
+}
+
+pub fn normalise(raw: &str) -> Normalised {
+    let mut fixes = Vec::new();
+    let raw = match raw.strip_prefix('\u{feff}') {
+        Some(rest) => {
+            fixes.push("byte order mark removed".to_string());
+            rest
+        }
+        None => raw,
+    };
+    if raw.contains("\r\n") {
+        fixes.push("CRLF line endings".to_string());
+    }
+
+    let mut lines: Vec<(usize, &str)> = Vec::new();
+    let mut blanks = 0;
+    for (i, line) in raw.split('\n').enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim().is_empty() {
+            if !line.is_empty() {
+                fixes.push(format!("line {}: whitespace on a blank line", i + 1));
+            }
+            blanks += 1;
+            continue;
+        }
+        if blanks > 0 {
+            if lines.is_empty() {
+                fixes.push(format!("{} blank lines at the start", blanks));
+            } else {
+                if blanks > 1 {
+                    fixes.push(format!("line {}: {} blank lines before it", i + 1, blanks));
+                }
+                lines.push((i, ""));
+            }
+            blanks = 0;
+        }
+        lines.push((i + 1, line));
+    }
+    // The last split is what follows the final newline, normally nothing.
+    match blanks {
+        0 if !lines.is_empty() => fixes.push("no newline at the end".to_string()),
+        0 | 1 => (),
+        n => fixes.push(format!("{} blank lines at the end", n - 1)),
+    }
+
+    let mut text = String::with_capacity(raw.len() + 1);
+    for (_, line) in &lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    Normalised {
+        text,
+        line_numbers: lines.into_iter().map(|(n, _)| n).collect(),
+        fixes,
+    }
+}
+
+/// Reads a day's input, relative to the year's directory, normalised. While
+/// the day runs on a sample, reading its puzzle input (`input.txt`, or
+/// `part1.txt` for days 1 to 3) reads the sample instead, so days don't need
+/// to know which they were given.
+pub fn read(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    let raw = match runner::sample() {
+        Some(k) if is_puzzle_input(path) => {
+            fs::read_to_string(path.with_file_name(answers::sample_file_name(k)))?
+        }
+        _ => fs::read_to_string(path)?,
+    };
+    Ok(normalise(&raw).text)
+}
+
+/// Reads an input made of two sections separated by a blank line.
+pub fn read_sections(path: impl AsRef<Path>) -> io::Result<(String, String)> {
+    let content = read(path)?;
+    match content.split_once("\n\n") {
+        Some((first, second)) => Ok((first.to_string(), second.to_string())),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected two sections separated by a blank line",
+        )),
+    }
+}
+
+/// Whether `path` is a day's puzzle input, which a sample stands in for.
+fn is_puzzle_input(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "input.txt" || name == "part1.txt")
+}
+
+/// What a day's input looks like. Line patterns are literal text with
+/// placeholders:
+///
+/// - `<int>` and `<uint>`: a number, with or without a sign
+/// - `<word>`: letters and digits
+/// - `<ints>`, `<ints,>`, `<words,>`: one or more, separated by spaces or
+///   by commas
+///
+/// A space in a pattern matches any run of spaces.
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    /// Anything at all.
+    Text,
+    /// A single line.
+    Line(&'static str),
+    /// Any number of lines, all alike.
+    Lines(&'static str),
+    /// Exactly these lines, in order.
+    Rows(&'static [&'static str]),
+    /// Lines of any length made of these characters.
+    Chars(&'static str),
+    /// Lines of one length made of these characters, or of any non-space
+    /// characters if empty.
+    Grid(&'static str),
+    /// One section per shape, separated by blank lines.
+    Sections(&'static [Shape]),
+    /// Any number of blocks of the same shape, separated by blank lines.
+    Repeated(&'static Shape),
+}
+
+/// A problem with one line of an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Shape {
+    /// Everything about `input` that doesn't fit, by line.
+    pub fn check(&self, input: &Normalised) -> Vec<Diagnostic> {
+        let lines = input.lines();
+        let mut out = Vec::new();
+        match self {
+            Shape::Sections(_) | Shape::Repeated(_) | Shape::Text => {
+                self.check_lines(&lines, &mut out)
+            }
+            _ => match lines.iter().find(|(_, line)| line.is_empty()) {
+                Some(&(n, _)) => out.push(diagnostic(n, "unexpected blank line".to_string())),
+                None => self.check_lines(&lines, &mut out),
+            },
+        }
+        out.sort_by_key(|d| d.line);
+        out
+    }
+
+    fn check_lines(&self, lines: &[(usize, &str)], out: &mut Vec<Diagnostic>) {
+        let end = lines.last().map_or(1, |&(n, _)| n + 1);
+        match *self {
+            Shape::Text => (),
+            Shape::Line(pattern) => {
+                if let Some(&(n, _)) = lines.get(1) {
+                    out.push(diagnostic(
+                        n,
+                        "expected the input to be one line".to_string(),
+                    ));
+                }
+                match lines.first() {
+                    Some(&(n, line)) => check_pattern(pattern, n, line, out),
+                    None => out.push(diagnostic(end, format!("expected `{}`", pattern))),
+                }
+            }
+            Shape::Lines(pattern) => {
+                if lines.is_empty() {
+                    out.push(diagnostic(end, format!("expected `{}`", pattern)));
+                }
+                for &(n, line) in lines {
+                    check_pattern(pattern, n, line, out);
+                }
+            }
+            Shape::Rows(patterns) => {
+                for (i, pattern) in patterns.iter().enumerate() {
+                    match lines.get(i) {
+                        Some(&(n, line)) => check_pattern(pattern, n, line, out),
+                        None => out.push(diagnostic(end, format!("expected `{}`", pattern))),
+                    }
+                }
+                if let Some(&(n, _)) = lines.get(patterns.len()) {
+                    out.push(diagnostic(
+                        n,
+                        format!("expected only {} lines here", patterns.len()),
+                    ));
+                }
+            }
+            Shape::Chars(chars) => {
+                for &(n, line) in lines {
+                    check_chars(chars, n, line, out);
+                }
+            }
+            Shape::Grid(chars) => {
+                let Some((first, width)) = lines.first().map(|&(n, l)| (n, l.chars().count()))
+                else {
+                    out.push(diagnostic(end, "expected a grid".to_string()));
+                    return;
+                };
+                for &(n, line) in lines {
+                    check_chars(chars, n, line, out);
+                    let len = line.chars().count();
+                    if len != width {
+                        out.push(diagnostic(
+                            n,
+                            format!(
+                                "expected {} columns like line {}, got {}",
+                                width, first, len
+                            ),
+                        ));
+                    }
+                }
+            }
+            Shape::Sections(shapes) => check_sections(shapes, lines, out),
+            Shape::Repeated(shape) => {
+                for block in blocks(lines) {
+                    shape.check_lines(block, out);
+                }
+            }
+        }
+    }
+}
+
+fn diagnostic(line: usize, message: String) -> Diagnostic {
+    Diagnostic { line, message }
+}
+
+/// The runs of lines between blank lines.
+fn blocks<'a, 'b>(lines: &'a [(usize, &'b str)]) -> Vec<&'a [(usize, &'b str)]> {
+    lines
+        .split(|(_, line)| line.is_empty())
+        .filter(|block| !block.is_empty())
+        .collect()
+}
+
+/// Checks each block against its section's shape. A block that starts out
+/// as one section and carries on as the next is missing the blank line
+/// between them, and is reported as just that.
+fn check_sections(shapes: &[Shape], lines: &[(usize, &str)], out: &mut Vec<Diagnostic>) {
+    let blocks = blocks(lines);
+    let mut next = 0;
+    for block in &blocks {
+        let Some(shape) = shapes.get(next) else {
+            out.push(diagnostic(
+                block[0].0,
+                format!("expected only {} sections", shapes.len()),
+            ));
+            return;
+        };
+        let mut problems = Vec::new();
+        shape.check_lines(block, &mut problems);
+
+        if let (Some(first), Some(following)) = (problems.first(), shapes.get(next + 1)) {
+            let split = block.iter().position(|&(n, _)| n == first.line);
+            if let Some(at) = split.filter(|&at| at > 0) {
+                let (mut before, mut after) = (Vec::new(), Vec::new());
+                shape.check_lines(&block[..at], &mut before);
+                following.check_lines(&block[at..], &mut after);
+                if before.is_empty() && after.is_empty() {
+                    out.push(diagnostic(
+                        first.line,
+                        "expected a blank line before this one, to start the next section"
+                            .to_string(),
+                    ));
+                    next += 2;
+                    continue;
+                }
+            }
+        }
+        out.extend(problems);
+        next += 1;
+    }
+
+    if next < shapes.len() {
+        let end = lines.last().map_or(1, |&(n, _)| n + 1);
+        out.push(diagnostic(
+            end,
+            format!(
+                "expected {} sections separated by blank lines, found {}",
+                shapes.len(),
+                blocks.len()
+            ),
+        ));
+    }
+}
+
+fn check_pattern(pattern: &str, n: usize, line: &str, out: &mut Vec<Diagnostic>) {
+    if !pattern_regex(pattern).is_match(line) {
+        out.push(diagnostic(n, format!("expected `{}`", pattern)));
+    }
+}
+
+fn check_chars(chars: &str, n: usize, line: &str, out: &mut Vec<Diagnostic>) {
+    let allowed = |c: char| match chars {
+        "" => !c.is_whitespace(),
+        chars => chars.contains(c),
+    };
+    if let Some((column, c)) = line.chars().enumerate().find(|&(_, c)| !allowed(c)) {
+        let expected = match chars {
+            "" => "a visible character".to_string(),
+            chars => format!("one of `{}`", chars),
+        };
+        out.push(diagnostic(
+            n,
+            format!(
+                "column {}: unexpected {:?}, expected {}",
+                column + 1,
+                c,
+                expected
+            ),
+        ));
+    }
+}
+
+thread_local! {
+    /// Patterns are checked once per line, and per block in repeated shapes.
+    static REGEXES: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
+}
+
+/// The regex for a line pattern, matching the whole line.
+fn pattern_regex(pattern: &str) -> Regex {
+    if let Some(re) = REGEXES.with(|r| r.borrow().get(pattern).cloned()) {
+        return re;
+    }
+    let re = compile_pattern(pattern);
+    REGEXES.with(|r| r.borrow_mut().insert(pattern.to_string(), re.clone()));
+    re
+}
+
+fn compile_pattern(pattern: &str) -> Regex {
+    const INT: &str = r"[+-]?\d+";
+    const WORD: &str = r"[A-Za-z0-9]+";
+    let mut re = String::from("^");
+    let mut rest = pattern;
+    while !rest.is_empty() {
+        let placeholder = rest
+            .strip_prefix('<')
+            .and_then(|r| r.split_once('>'))
+            .and_then(|(name, after)| {
+                let re = match name {
+                    "int" => INT.to_string(),
+                    "uint" => r"\d+".to_string(),
+                    "word" => WORD.to_string(),
+                    "ints" => format!("{0}(?: +{0})*", INT),
+                    "ints," => format!("{0}(?:, *{0})*", INT),
+                    "words," => format!("{0}(?:, *{0})*", WORD),
+                    _ => return None,
+                };
+                Some((re, after))
+            });
+        match placeholder {
+            Some((placeholder, after)) => {
+                re.push_str(&placeholder);
+                rest = after;
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                match c {
+                    ' ' => re.push_str(" +"),
+                    c => re.push_str(&regex::escape(&c.to_string())),
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    re.push('$');
+    Regex::new(&re).expect("input patterns make valid regexes")
+}
+
+/// `aoc check-input`: prints what normalising `path` changes and where it
+/// doesn't fit `shape`. Returns whether it fits.
+pub fn check_file(path: &Path, shape: &Shape) -> io::Result<bool> {
+    let input = normalise(&fs::read_to_string(path)?);
+    println!("Checking {}", path.display());
+    for fix in &input.fixes {
+        println!("  normalised: {}", fix);
+    }
+    let problems = shape.check(&input);
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    match problems.len() {
+        0 => println!("Looks fine"),
+        1 => println!("1 problem"),
+        n => println!("{} problems", n),
+    }
+    Ok(problems.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The diagnostics for `raw` as `aoc check-input` prints them.
+    fn problems(shape: &Shape, raw: &str) -> Vec<String> {
+        shape
+            .check(&normalise(raw))
+            .iter()
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn crlf_and_byte_order_mark() {
+        let input = normalise("\u{feff}1 2\r\n3 4\r\n");
+        assert_eq!(input.text, "1 2\n3 4\n");
+        assert_eq!(input.line_numbers, [1, 2]);
+        assert_eq!(
+            input.fixes,
+            ["byte order mark removed", "CRLF line endings"]
+        );
+
+        let shape = Shape::Lines("<uint> <uint>");
+        assert!(problems(&shape, "\u{feff}1 2\r\n3 4\r\n").is_empty());
+        assert_eq!(
+            problems(&shape, "\u{feff}1 2\r\nx 4\r\n"),
+            ["line 2: expected `<uint> <uint>`"]
+        );
+    }
+
+    #[test]
+    fn blank_lines_keep_original_line_numbers() {
+        let input = normalise("ab\n\n\n\ncd\nxy!\n\n\n");
+        assert_eq!(input.text, "ab\n\ncd\nxy!\n");
+        assert_eq!(input.line_numbers, [1, 4, 5, 6]);
+        assert_eq!(
+            input.fixes,
+            [
+                "line 5: 3 blank lines before it",
+                "2 blank lines at the end"
+            ]
+        );
+
+        let shape = Shape::Repeated(&Shape::Chars("abcdxy"));
+        assert_eq!(
+            problems(&shape, "ab\n\n\n\ncd\nxy!\n\n\n"),
+            ["line 6: column 3: unexpected '!', expected one of `abcdxy`"]
+        );
+    }
+
+    #[test]
+    fn section_running_into_the_next() {
+        let shape = Shape::Sections(&[Shape::Lines("<uint>|<uint>"), Shape::Lines("<ints,>")]);
+        assert!(problems(&shape, "1|2\n3|4\n\n5,6\n7,8\n").is_empty());
+        assert_eq!(
+            problems(&shape, "1|2\n3|4\n5,6\n7,8\n"),
+            ["line 3: expected a blank line before this one, to start the next section"]
+        );
+    }
+
+    #[test]
+    fn grid_width_mismatch() {
+        assert_eq!(
+            problems(&Shape::Grid(".#"), "..#\n.#\n..#\n"),
+            ["line 2: expected 3 columns like line 1, got 2"]
+        );
+    }
+}
@@ -0,0 +1,18 @@
+#[cfg(feature = "alloc-stats")]
+pub mod alloc_stats;
+pub mod answers;
+pub mod bench;
+pub mod budget;
+pub mod gf2;
+pub mod graph;
+pub mod grid;
+pub mod guesses;
+pub mod image;
+pub mod input;
+pub mod linalg;
+pub mod profile;
+pub mod runner;
+pub mod scaffold;
+pub mod site;
+pub mod visualize;
+pub mod watch;
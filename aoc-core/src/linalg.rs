@@ -0,0 +1,305 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// An exact fraction kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Rational {
+        assert!(den != 0, "zero denominator");
+        let g = gcd(num, den).max(1);
+        let sign = den.signum();
+        Rational {
+            num: sign * num / g,
+            den: sign * den / g,
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn ceil(&self) -> i128 {
+        -(-self.num).div_euclid(self.den)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Rational {
+        Rational { num: n, den: 1 }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self, rhs: Rational) -> Rational {
+        let g = gcd(self.den, rhs.den);
+        let den = self.den / g * rhs.den;
+        Rational::new(self.num * (den / self.den) + rhs.num * (den / rhs.den), den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self, rhs: Rational) -> Rational {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self, rhs: Rational) -> Rational {
+        // Cross-reduce first so intermediate products stay small.
+        let a = gcd(self.num, rhs.den).max(1);
+        let b = gcd(rhs.num, self.den).max(1);
+        Rational::new(
+            (self.num / a) * (rhs.num / b),
+            (self.den / b) * (rhs.den / a),
+        )
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+    fn div(self, rhs: Rational) -> Rational {
+        assert!(!rhs.is_zero(), "division by zero");
+        self * Rational::new(rhs.den, rhs.num)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+/// Non-negative greatest common divisor; `gcd(0, 0) == 0`.
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns `(g, x, y)` with `g = gcd(a, b) >= 0` and `a * x + b * y = g`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+/// All integers `k` with `a * k ≡ b (mod m)`, as `k ≡ residue (mod modulus)`.
+/// `None` when there is no solution.
+pub fn solve_congruence(a: i128, b: i128, m: i128) -> Option<(i128, i128)> {
+    let m = m.abs();
+    if m == 0 {
+        // Plain equation a * k == b.
+        return match a {
+            0 if b == 0 => Some((0, 1)),
+            0 => None,
+            _ if b % a == 0 => Some((b / a, 0)),
+            _ => None,
+        };
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if b.rem_euclid(g) != 0 {
+        return None;
+    }
+    let modulus = m / g;
+    let residue = ((b / g).rem_euclid(modulus) * x.rem_euclid(modulus)).rem_euclid(modulus);
+    Some((residue, modulus))
+}
+
+/// Combines `k ≡ r1 (mod m1)` and `k ≡ r2 (mod m2)` with the Chinese Remainder
+/// Theorem; the moduli do not need to be coprime. A modulus of 0 pins `k` to
+/// exactly the residue.
+pub fn combine_congruences((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    match (m1, m2) {
+        (0, 0) => (r1 == r2).then_some((r1, 0)),
+        (0, _) => ((r1 - r2).rem_euclid(m2) == 0).then_some((r1, 0)),
+        (_, 0) => ((r2 - r1).rem_euclid(m1) == 0).then_some((r2, 0)),
+        _ => {
+            let (g, p, _) = extended_gcd(m1, m2);
+            if (r2 - r1) % g != 0 {
+                return None;
+            }
+            let lcm = m1 / g * m2;
+            let k = ((r2 - r1) / g % (m2 / g) * p).rem_euclid(m2 / g);
+            Some(((r1 + m1 * k).rem_euclid(lcm), lcm))
+        }
+    }
+}
+
+/// The solution set of `A x = b` as `x = particular + Σ t_k * basis[k]`, where
+/// `t_k` is the value of the free variable `free[k]`.
+#[derive(Debug, Clone)]
+pub struct AffineSolution {
+    pub particular: Vec<Rational>,
+    pub free: Vec<usize>,
+    pub basis: Vec<Vec<Rational>>,
+}
+
+impl AffineSolution {
+    /// Fixes the free variable `free[k]` to `value`, returning the solution set
+    /// of the remaining free variables.
+    pub fn fix(&self, k: usize, value: Rational) -> AffineSolution {
+        let particular = self
+            .particular
+            .iter()
+            .zip(self.basis[k].iter())
+            .map(|(&p, &d)| p + d * value)
+            .collect();
+        let mut free = self.free.clone();
+        let mut basis = self.basis.clone();
+        free.remove(k);
+        basis.remove(k);
+        AffineSolution {
+            particular,
+            free,
+            basis,
+        }
+    }
+}
+
+/// Solves `A x = b` exactly by Gauss-Jordan elimination over the rationals.
+/// Returns `None` when the system is inconsistent.
+pub fn solve(a: &[Vec<i128>], b: &[i128]) -> Option<AffineSolution> {
+    let rows = a.len();
+    let cols = a.first().map_or(0, |r| r.len());
+
+    let mut m: Vec<Vec<Rational>> = a
+        .iter()
+        .zip(b.iter())
+        .map(|(row, &rhs)| {
+            row.iter()
+                .map(|&v| Rational::from(v))
+                .chain(std::iter::once(Rational::from(rhs)))
+                .collect()
+        })
+        .collect();
+
+    let mut pivots = Vec::new();
+    let mut r = 0;
+    for c in 0..cols {
+        let Some(p) = (r..rows).find(|&i| !m[i][c].is_zero()) else {
+            continue;
+        };
+        m.swap(r, p);
+
+        let pivot = m[r][c];
+        for v in m[r].iter_mut() {
+            *v = *v / pivot;
+        }
+        let pivot_row = m[r].clone();
+        for (i, row) in m.iter_mut().enumerate() {
+            if i != r && !row[c].is_zero() {
+                let factor = row[c];
+                for (v, &p) in row.iter_mut().zip(pivot_row.iter()).skip(c) {
+                    *v = *v - factor * p;
+                }
+            }
+        }
+
+        pivots.push(c);
+        r += 1;
+        if r == rows {
+            break;
+        }
+    }
+
+    // A zero row with a non-zero right hand side means no solution.
+    if m[r..].iter().any(|row| !row[cols].is_zero()) {
+        return None;
+    }
+
+    let free: Vec<usize> = (0..cols).filter(|c| !pivots.contains(c)).collect();
+
+    let mut particular = vec![Rational::ZERO; cols];
+    for (i, &c) in pivots.iter().enumerate() {
+        particular[c] = m[i][cols];
+    }
+
+    let basis = free
+        .iter()
+        .map(|&f| {
+            let mut v = vec![Rational::ZERO; cols];
+            v[f] = Rational::ONE;
+            for (i, &c) in pivots.iter().enumerate() {
+                v[c] = -m[i][f];
+            }
+            v
+        })
+        .collect();
+
+    Some(AffineSolution {
+        particular,
+        free,
+        basis,
+    })
+}
@@ -0,0 +1,166 @@
+//! A small span profiler. Days mark regions with [`span!`](crate::span),
+//! which times the rest of the enclosing block, and the runner prints the
+//! resulting tree after a day when given `--profile`. Spans are only
+//! recorded with the `profile` feature; without it `span!` expands to
+//! nothing.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+struct Node {
+    name: &'static str,
+    children: Vec<usize>,
+    parent: Option<usize>,
+    calls: u64,
+    total: Duration,
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+    current: usize,
+}
+
+impl Tree {
+    fn new() -> Tree {
+        Tree {
+            nodes: vec![Node {
+                name: "",
+                children: Vec::new(),
+                parent: None,
+                calls: 0,
+                total: Duration::ZERO,
+            }],
+            current: 0,
+        }
+    }
+
+    fn child(&mut self, name: &'static str) -> usize {
+        let parent = self.current;
+        if let Some(&i) = self.nodes[parent]
+            .children
+            .iter()
+            .find(|&&i| self.nodes[i].name == name)
+        {
+            return i;
+        }
+        self.nodes.push(Node {
+            name,
+            children: Vec::new(),
+            parent: Some(parent),
+            calls: 0,
+            total: Duration::ZERO,
+        });
+        let i = self.nodes.len() - 1;
+        self.nodes[parent].children.push(i);
+        i
+    }
+}
+
+thread_local! {
+    static TREE: RefCell<Tree> = RefCell::new(Tree::new());
+}
+
+/// An open span, closed when dropped. Use [`span!`](crate::span) rather than
+/// this directly.
+pub struct Span {
+    node: usize,
+    /// `None` for a span re-entered by direct recursion, which is counted
+    /// but folded into the outermost call's time.
+    start: Option<Instant>,
+}
+
+impl Span {
+    pub fn enter(name: &'static str) -> Span {
+        TREE.with(|t| {
+            let mut tree = t.borrow_mut();
+            let current = tree.current;
+            if current != 0 && tree.nodes[current].name == name {
+                tree.nodes[current].calls += 1;
+                return Span {
+                    node: current,
+                    start: None,
+                };
+            }
+
+            let node = tree.child(name);
+            tree.current = node;
+            Span {
+                node,
+                start: Some(Instant::now()),
+            }
+        })
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+        let elapsed = start.elapsed();
+        TREE.with(|t| {
+            let mut tree = t.borrow_mut();
+            let node = &mut tree.nodes[self.node];
+            node.calls += 1;
+            node.total += elapsed;
+            tree.current = node.parent.unwrap_or(0);
+        });
+    }
+}
+
+/// Forgets all spans, ready for the next day.
+pub fn reset() {
+    TREE.with(|t| *t.borrow_mut() = Tree::new());
+}
+
+/// Prints the span tree with call counts, total time, and self time (total
+/// less the time in child spans).
+pub fn print() {
+    TREE.with(|t| {
+        let tree = t.borrow();
+        if tree.nodes[0].children.is_empty() {
+            println!("  no spans recorded");
+            return;
+        }
+        println!(
+            "  {:<32} {:>10} {:>14} {:>14}",
+            "span", "calls", "total", "self"
+        );
+        let mut stack: Vec<(usize, usize)> = tree.nodes[0]
+            .children
+            .iter()
+            .rev()
+            .map(|&i| (i, 0))
+            .collect();
+        while let Some((i, depth)) = stack.pop() {
+            let node = &tree.nodes[i];
+            let children: Duration = node.children.iter().map(|&c| tree.nodes[c].total).sum();
+            let label = format!("{}{}", "  ".repeat(depth), node.name);
+            println!(
+                "  {:<32} {:>10} {:>14} {:>14}",
+                label,
+                node.calls,
+                format!("{:.2?}", node.total),
+                format!("{:.2?}", node.total.saturating_sub(children))
+            );
+            stack.extend(node.children.iter().rev().map(|&c| (c, depth + 1)));
+        }
+    });
+}
+
+/// Times the rest of the enclosing block as a span called `$name`.
+#[cfg(feature = "profile")]
+#[macro_export]
+macro_rules! span {
+    ($name:expr) => {
+        let _span = $crate::profile::Span::enter($name);
+    };
+}
+
+/// Times the rest of the enclosing block as a span called `$name`; without
+/// the `profile` feature this is nothing at all.
+#[cfg(not(feature = "profile"))]
+#[macro_export]
+macro_rules! span {
+    ($name:expr) => {};
+}
@@ -0,0 +1,326 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "alloc-stats")]
+use crate::alloc_stats;
+use crate::answers;
+use crate::input::{self, Shape};
+use crate::profile;
+use crate::site;
+
+pub type DayResult = Result<(), Box<dyn std::error::Error>>;
+
+/// A registered day. Each day module exposes `run`, which takes the year's
+/// command-line options `O` and picks out its own, and its input's shape as
+/// `INPUT`, and reports its answers through [`answer`].
+pub struct Solution<O: 'static> {
+    pub day: u32,
+    pub run: fn(&O) -> DayResult,
+    pub input: Shape,
+}
+
+impl<O> Solution<O> {
+    pub const fn new(day: u32, input: Shape, run: fn(&O) -> DayResult) -> Solution<O> {
+        Solution { day, run, input }
+    }
+}
+
+/// How `--day` and `--all` run days, apart from the days' own options.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RunOptions {
+    /// Check the answers against the known ones.
+    pub verify: bool,
+    /// Run on this sample instead of the input; only for a single day.
+    pub sample: Option<u32>,
+    /// Print the spans recorded by `span!` after each day.
+    pub profile: bool,
+}
+
+/// One year's solutions. Days read their inputs relative to `dir`, the year's
+/// directory with its `dayN/` directories.
+pub struct Year<O: 'static> {
+    pub year: u32,
+    pub dir: PathBuf,
+    /// Where the year's crate was built, for `aoc watch` to find its sources.
+    pub crate_dir: PathBuf,
+    pub solutions: &'static [Solution<O>],
+}
+
+impl<O> Year<O> {
+    /// The year's solutions, reading their inputs from `dir` if given. Else
+    /// the working directory is used if it is the year's directory or holds
+    /// it, as the root of a checkout does, and failing that `built_in`, where
+    /// the year's crate was built.
+    pub fn locate(
+        year: u32,
+        solutions: &'static [Solution<O>],
+        built_in: &str,
+        dir: Option<&Path>,
+    ) -> io::Result<Year<O>> {
+        let dir = match dir {
+            Some(dir) => std::path::absolute(dir)?,
+            None => {
+                let name = year.to_string();
+                let cwd = std::env::current_dir()?;
+                if cwd.file_name().is_some_and(|n| n == name.as_str()) {
+                    cwd
+                } else if cwd.join(&name).is_dir() {
+                    cwd.join(name)
+                } else {
+                    PathBuf::from(built_in)
+                }
+            }
+        };
+        Ok(Year {
+            year,
+            dir,
+            crate_dir: PathBuf::from(built_in),
+            solutions,
+        })
+    }
+
+    pub fn find(&self, day: u32) -> Option<&'static Solution<O>> {
+        self.solutions.iter().find(|s| s.day == day)
+    }
+
+    /// Makes the year's directory the working directory, so `dayN/...`
+    /// paths resolve wherever the binary was started from.
+    pub fn enter(&self) -> io::Result<()> {
+        std::env::set_current_dir(&self.dir)
+    }
+
+    /// Enters the year's directory to run `day`, on `sample` if given, which
+    /// must exist. The day reads its input normalised, and the sample in
+    /// place of its input, through [`input::read`], so nothing is copied
+    /// and paths in the day's options stay relative to the year's directory.
+    ///
+    /// Also warns when the day's input doesn't fit its shape; the day still
+    /// runs, as the shape may be stricter than the day.
+    pub fn enter_day(&self, day: u32, sample: Option<u32>) -> io::Result<()> {
+        self.enter()?;
+        match sample {
+            Some(k) => {
+                let path = answers::sample_path(day, k);
+                if !path.exists() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("no sample {}", path.display()),
+                    ));
+                }
+            }
+            None => {
+                // A missing input is left for the day to report.
+                let path = site::input_path(day);
+                if let Ok(raw) = fs::read_to_string(&path) {
+                    self.warn_about_input(day, &path, &input::normalise(&raw));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the first few ways `input` doesn't fit the day's shape.
+    fn warn_about_input(&self, day: u32, path: &Path, input: &input::Normalised) {
+        let Some(solution) = self.find(day) else {
+            return;
+        };
+        let problems = solution.input.check(input);
+        if problems.is_empty() {
+            return;
+        }
+        println!("Day {} input {} doesn't look right:", day, path.display());
+        for problem in problems.iter().take(3) {
+            println!("  {}", problem);
+        }
+        if problems.len() > 3 {
+            println!(
+                "  and {} more; see `aoc check-input {}`",
+                problems.len() - 3,
+                day
+            );
+        }
+    }
+
+    /// Runs one day with its runtime. Returns false if it failed, or if
+    /// verifying and an answer is known to be wrong.
+    pub fn run_day(&self, day: u32, options: &O, run_options: &RunOptions) -> bool {
+        let start = Instant::now();
+        if let Err(e) = self.enter_day(day, run_options.sample) {
+            match run_options.sample {
+                Some(k) => println!("Could not use sample {} for day {}: {}", k, day, e),
+                None => println!("Could not read the input for day {}: {}", day, e),
+            }
+            return false;
+        }
+        let ok = match self.find(day) {
+            Some(solution) => run_checked(solution, options, run_options),
+            None => {
+                println!("Solution for day {} is not implemented yet.", day);
+                true
+            }
+        };
+        println!("Total runtime: {:?}", start.elapsed());
+        print_stats(run_options);
+        ok
+    }
+
+    /// Runs every day on its input, timing each, as [`Year::run_day`] does.
+    pub fn run_all(&self, options: &O, run_options: &RunOptions) -> bool {
+        let run_options = RunOptions {
+            sample: None,
+            ..*run_options
+        };
+        let total = Instant::now();
+        let mut ok = true;
+
+        for solution in self.solutions {
+            let start = Instant::now();
+            if let Err(e) = self.enter_day(solution.day, None) {
+                println!("Could not read the input for day {}: {}", solution.day, e);
+                ok = false;
+                continue;
+            }
+            ok &= run_checked(solution, options, &run_options);
+            println!("Day {} runtime: {:?}", solution.day, start.elapsed());
+            print_stats(&run_options);
+        }
+        let elapsed = total.elapsed();
+        println!("Total runtime: {:?}", elapsed);
+        println!(
+            "Average runtime: {:?}",
+            elapsed / self.solutions.len().max(1) as u32
+        );
+        ok
+    }
+}
+
+fn run_checked<O>(solution: &Solution<O>, options: &O, run_options: &RunOptions) -> bool {
+    SAMPLE.with(|s| s.set(run_options.sample));
+    let checked = if run_options.verify {
+        run_for_answers(solution, options).and_then(|reported| {
+            Ok(answers::verify(
+                solution.day,
+                run_options.sample,
+                &reported,
+            )?)
+        })
+    } else {
+        run(solution, options).map(|_| true)
+    };
+    SAMPLE.with(|s| s.set(None));
+    match checked {
+        Ok(ok) => ok,
+        Err(e) => {
+            println!("Error in day {}: {:?}", solution.day, e);
+            false
+        }
+    }
+}
+
+/// What was measured of the day just run, beyond its runtime: allocations
+/// with the `alloc-stats` feature, and spans with `--profile`.
+fn print_stats(run_options: &RunOptions) {
+    #[cfg(feature = "alloc-stats")]
+    if let Some(stats) = alloc_stats::last_day() {
+        println!("  {}", stats.total);
+        for (part, stats) in stats.parts {
+            println!("    part {}: {}", part, stats);
+        }
+    }
+
+    if run_options.profile {
+        if cfg!(feature = "profile") {
+            profile::print();
+        } else {
+            println!("  no spans: build with `--features profile` to record them");
+        }
+    }
+}
+
+thread_local! {
+    /// The day being run, so answers can be labelled with it.
+    static CURRENT_DAY: Cell<Option<u32>> = const { Cell::new(None) };
+
+    /// The sample the current day is running on, if not its input.
+    static SAMPLE: Cell<Option<u32>> = const { Cell::new(None) };
+
+    /// Every answer reported since the current day started, by part.
+    static ANSWERS: RefCell<Vec<(u32, String)>> = const { RefCell::new(Vec::new()) };
+
+    /// Set while benchmarking, so the repeated runs don't print answers.
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+
+    /// When the current part started: with the day, then at each answer.
+    static PART_START: Cell<Option<Instant>> = const { Cell::new(None) };
+
+    /// How long each part took up to its answer, in the order reported.
+    static PART_TIMES: RefCell<Vec<(u32, Duration)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn run<O>(solution: &Solution<O>, options: &O) -> DayResult {
+    ANSWERS.with(|a| a.borrow_mut().clear());
+    PART_TIMES.with(|t| t.borrow_mut().clear());
+    CURRENT_DAY.with(|d| d.set(Some(solution.day)));
+    profile::reset();
+    #[cfg(feature = "alloc-stats")]
+    alloc_stats::begin_day();
+    PART_START.with(|s| s.set(Some(Instant::now())));
+    let result = (solution.run)(options);
+    PART_START.with(|s| s.set(None));
+    #[cfg(feature = "alloc-stats")]
+    alloc_stats::end_day();
+    CURRENT_DAY.with(|d| d.set(None));
+    result
+}
+
+/// Runs a day and returns the answers it reported, in order.
+pub fn run_for_answers<O>(
+    solution: &Solution<O>,
+    options: &O,
+) -> Result<Vec<(u32, String)>, Box<dyn std::error::Error>> {
+    run(solution, options)?;
+    Ok(ANSWERS.with(|a| a.take()))
+}
+
+/// Runs a day without printing its answers, and returns how long each part
+/// took. Part 1's time runs from the start of the day, so it includes
+/// parsing; each later part's runs from the answer before it.
+pub fn run_quietly<O>(
+    solution: &Solution<O>,
+    options: &O,
+) -> Result<Vec<(u32, Duration)>, Box<dyn std::error::Error>> {
+    QUIET.with(|q| q.set(true));
+    let result = run(solution, options);
+    QUIET.with(|q| q.set(false));
+    result?;
+    Ok(PART_TIMES.with(|t| t.take()))
+}
+
+/// The sample the day being run was given in place of its input, for days
+/// whose puzzle states something about the input that the file doesn't.
+pub fn sample() -> Option<u32> {
+    SAMPLE.with(|s| s.get())
+}
+
+/// Reports the answer to one part of the day being run.
+pub fn answer(part: u32, value: impl fmt::Display) {
+    #[cfg(feature = "alloc-stats")]
+    alloc_stats::end_part(part);
+    if let Some(start) = PART_START.with(|s| s.get()) {
+        let now = Instant::now();
+        PART_START.with(|s| s.set(Some(now)));
+        PART_TIMES.with(|t| t.borrow_mut().push((part, now - start)));
+    }
+    ANSWERS.with(|a| a.borrow_mut().push((part, value.to_string())));
+    if QUIET.with(|q| q.get()) {
+        return;
+    }
+    match CURRENT_DAY.with(|d| d.get()) {
+        Some(day) => println!("Day {} Part {}: {}", day, part, value),
+        None => println!("Part {}: {}", part, value),
+    }
+}
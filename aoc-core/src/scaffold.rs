@@ -0,0 +1,196 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The module every new day starts from, with `{DAY}` for the day number.
+const TEMPLATE: &str = r#"use aoc_core::input::{self, Shape};
+use aoc_core::runner::answer;
+
+/// Checked by `aoc check-input {DAY}`; describe the input once it's known.
+pub const INPUT: Shape = Shape::Text;
+
+/// Day {DAY}. Using `day{DAY}/sample.txt` gives the answers in
+/// `day{DAY}/sample.answers`.
+pub fn run(_args: &crate::Options) -> Result<(), Box<dyn std::error::Error>> {
+    let data = input::read("day{DAY}/input.txt")?;
+
+    answer(1, part1(&data));
+    answer(2, part2(&data));
+
+    Ok(())
+}
+
+fn part1(_data: &str) -> usize {
+    0
+}
+
+fn part2(_data: &str) -> usize {
+    0
+}
+"#;
+
+/// Sets up day `day` in the year crate at `root`:
+///
+/// - `src/days/dayNN.rs` from the template
+/// - `pub mod dayNN;` and a `SOLUTIONS` entry in `src/days/mod.rs`
+/// - `dayN/` with an empty `sample.txt` and `sample.answers` (one
+///   `part: answer` line per part, filled in by hand)
+/// - a row in the README's runtime table
+///
+/// Refuses if the day is already registered or its module exists. Every edit
+/// is worked out before anything is written, so a failure leaves the tree as
+/// it was.
+pub fn new_day(root: &Path, day: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let module = format!("day{:02}", day);
+    let module_path = root.join("src/days").join(format!("{}.rs", module));
+    let mod_rs = root.join("src/days/mod.rs");
+    let readme = root.join("README.md");
+
+    let mod_text = fs::read_to_string(&mod_rs)?;
+    let in_registry = mod_text
+        .lines()
+        .any(|l| l.trim_start().starts_with("Solution::new(") && numbered_line(l) == Some(day));
+    if in_registry || module_path.exists() {
+        return Err(already_exists(format!("day {} already exists", day)));
+    }
+
+    let registered = register(&mod_text, day, &module)?;
+    let readme_text = fs::read_to_string(&readme).ok();
+    let readme_row = readme_text.as_deref().and_then(|t| add_readme_row(t, day));
+
+    let source = TEMPLATE.replace("{DAY}", &day.to_string());
+    create_new(&module_path, &source)?;
+    fs::write(&mod_rs, registered)?;
+    println!("created {}", module_path.display());
+    println!("registered {} in {}", module, mod_rs.display());
+
+    let data_dir = root.join(format!("day{}", day));
+    fs::create_dir_all(&data_dir)?;
+    for file in ["sample.txt", "sample.answers"] {
+        let path = data_dir.join(file);
+        match create_new(&path, "") {
+            Ok(()) => println!("created {}", path.display()),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                println!("kept existing {}", path.display())
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    match readme_row {
+        Some(text) => {
+            fs::write(&readme, text)?;
+            println!("added day {} to {}", day, readme.display());
+        }
+        None => println!(
+            "could not find the runtime table in README.md; add day {} by hand",
+            day
+        ),
+    }
+
+    Ok(())
+}
+
+fn already_exists(message: String) -> Box<dyn std::error::Error> {
+    Box::new(io::Error::new(io::ErrorKind::AlreadyExists, message))
+}
+
+fn create_new(path: &Path, contents: &str) -> io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
+/// The number in a line like `pub mod day07;` or `Solution::new(7, ...)`.
+fn numbered_line(line: &str) -> Option<u32> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("pub mod day") {
+        return rest.strip_suffix(';')?.parse().ok();
+    }
+    let rest = line.strip_prefix("Solution::new(")?;
+    rest[..rest.find(',')?].trim().parse().ok()
+}
+
+/// Adds the `pub mod` line and the `SOLUTIONS` entry for `day`.
+fn register(mod_rs: &str, day: u32, module: &str) -> io::Result<String> {
+    let mut lines: Vec<String> = mod_rs.lines().map(String::from).collect();
+    insert_sorted(
+        &mut lines,
+        "pub mod day",
+        format!("pub mod {};", module),
+        day,
+    )?;
+    insert_sorted(
+        &mut lines,
+        "Solution::new(",
+        format!(
+            "    Solution::new({}, {}::INPUT, {}::run),",
+            day, module, module
+        ),
+        day,
+    )?;
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Inserts `line` among the lines starting with `prefix`, before the first
+/// one for a later day.
+fn insert_sorted(lines: &mut Vec<String>, prefix: &str, line: String, day: u32) -> io::Result<()> {
+    let existing: Vec<usize> = (0..lines.len())
+        .filter(|&i| lines[i].trim_start().starts_with(prefix))
+        .collect();
+    let Some(&last) = existing.last() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("src/days/mod.rs has no line like `{}`", line.trim()),
+        ));
+    };
+    let at = existing
+        .into_iter()
+        .find(|&i| numbered_line(&lines[i]).is_some_and(|d| d > day))
+        .unwrap_or_else(|| end_of_entry(lines, last));
+    lines.insert(at, line);
+    Ok(())
+}
+
+/// The line after the entry starting at `start`. rustfmt can split a
+/// `SOLUTIONS` entry over several lines, which then end where its brackets
+/// close.
+fn end_of_entry(lines: &[String], start: usize) -> usize {
+    let mut depth = 0i32;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        for c in line.chars() {
+            match c {
+                '(' | '{' | '[' => depth += 1,
+                ')' | '}' | ']' => depth -= 1,
+                _ => (),
+            }
+        }
+        if depth <= 0 {
+            return i + 1;
+        }
+    }
+    lines.len()
+}
+
+/// Adds `| N | - | |` to the runtime table, before the first later day or the
+/// `...` row.
+fn add_readme_row(readme: &str, day: u32) -> Option<String> {
+    let mut lines: Vec<String> = readme.lines().map(String::from).collect();
+    let row_day = |line: &str| -> Option<Option<u32>> {
+        let cell = line.strip_prefix('|')?.split('|').next()?.trim();
+        match cell {
+            "..." => Some(None),
+            _ => cell.parse().ok().map(Some),
+        }
+    };
+
+    let at = lines.iter().position(|l| match row_day(l) {
+        Some(Some(d)) => d > day,
+        Some(None) => true,
+        None => false,
+    })?;
+    lines.insert(at, format!("| {:<7} | {:<13} | |", day, "-"));
+    Some(lines.join("\n") + "\n")
+}
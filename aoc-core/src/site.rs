@@ -0,0 +1,478 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use regex::Regex;
+
+use crate::answers::{self, Answers};
+use crate::guesses::{self, Guess, GuessLog, Outcome};
+
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const USER_AGENT: &str = "github.com/spkane31/advent-of-code by spkane31";
+
+/// The two requests we make of the puzzle site. `UreqClient` talks to the
+/// real thing; anything else (a stub server behind `AOC_BASE_URL`, a canned
+/// response) only needs these.
+pub trait Http {
+    fn get(&self, url: &str, session: &str) -> io::Result<String>;
+    fn post_form(&self, url: &str, session: &str, form: &[(&str, &str)]) -> io::Result<String>;
+}
+
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+
+impl UreqClient {
+    pub fn new() -> UreqClient {
+        UreqClient {
+            agent: ureq::AgentBuilder::new()
+                .user_agent(USER_AGENT)
+                .timeout(Duration::from_secs(30))
+                .build(),
+        }
+    }
+}
+
+impl Default for UreqClient {
+    fn default() -> Self {
+        UreqClient::new()
+    }
+}
+
+impl Http for UreqClient {
+    fn get(&self, url: &str, session: &str) -> io::Result<String> {
+        let request = self
+            .agent
+            .get(url)
+            .set("Cookie", &format!("session={}", session));
+        read_response(url, request.call())
+    }
+
+    fn post_form(&self, url: &str, session: &str, form: &[(&str, &str)]) -> io::Result<String> {
+        let request = self
+            .agent
+            .post(url)
+            .set("Cookie", &format!("session={}", session));
+        read_response(url, request.send_form(form))
+    }
+}
+
+fn read_response(url: &str, response: Result<ureq::Response, ureq::Error>) -> io::Result<String> {
+    match response {
+        Ok(response) => response.into_string(),
+        // The site answers 400 for a bad session and 404 for a puzzle that
+        // isn't out yet, with a one-line explanation in the body.
+        Err(ureq::Error::Status(code, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            Err(io::Error::other(format!(
+                "{} returned {}: {}",
+                url,
+                code,
+                body.lines().next().unwrap_or("").trim()
+            )))
+        }
+        Err(e) => Err(io::Error::other(e)),
+    }
+}
+
+/// What the site said about a submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, with no hint about which way.
+    Wrong,
+    /// Submitted too soon after the last answer; nothing was checked.
+    RateLimited(Duration),
+    /// The part is already solved, or part 2 isn't unlocked yet.
+    WrongLevel,
+    /// A page we don't know how to read, reduced to its text.
+    Unrecognised(String),
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "wrong, too high"),
+            Verdict::TooLow => write!(f, "wrong, too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::RateLimited(wait) => {
+                write!(f, "rate limited, try again in {}s", wait.as_secs())
+            }
+            Verdict::WrongLevel => write!(f, "already solved or not unlocked yet"),
+            Verdict::Unrecognised(text) => write!(f, "unrecognised response: {}", text),
+        }
+    }
+}
+
+/// Reads the verdict out of the page returned for a submitted answer.
+pub fn parse_verdict(page: &str) -> Verdict {
+    let text = article_text(page);
+
+    if text.contains("That's the right answer") {
+        Verdict::Correct
+    } else if text.contains("That's not the right answer") {
+        if text.contains("your answer is too high") {
+            Verdict::TooHigh
+        } else if text.contains("your answer is too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        }
+    } else if text.contains("You gave an answer too recently") {
+        // "You have 34s left to wait." or "You have 1m 5s left to wait."
+        let wait = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+        let seconds = wait
+            .captures(&text)
+            .map(|c| {
+                let minutes: u64 = c.get(1).map_or(0, |m| m.as_str().parse().unwrap());
+                minutes * 60 + c[2].parse::<u64>().unwrap()
+            })
+            .unwrap_or(60);
+        Verdict::RateLimited(Duration::from_secs(seconds))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Verdict::WrongLevel
+    } else {
+        Verdict::Unrecognised(text)
+    }
+}
+
+/// The text of the page's `<article>`, or of the whole page if it has none,
+/// with tags dropped and whitespace collapsed.
+fn article_text(page: &str) -> String {
+    let article = match (page.find("<article"), page.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &page[start..end],
+        _ => page,
+    };
+    let tags = Regex::new(r"<[^>]*>").unwrap();
+    tags.replace_all(article, "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The puzzle site for one year, reached through `http`.
+pub struct Site<H> {
+    http: H,
+    year: u32,
+    base_url: String,
+    session: String,
+}
+
+impl Site<UreqClient> {
+    /// The real site, or whatever `AOC_BASE_URL` points at, with the session
+    /// from [`session`].
+    pub fn from_env(year: u32) -> io::Result<Site<UreqClient>> {
+        let base_url = std::env::var("AOC_BASE_URL").unwrap_or(DEFAULT_BASE_URL.to_string());
+        Ok(Site::new(UreqClient::new(), year, base_url, session()?))
+    }
+}
+
+impl<H: Http> Site<H> {
+    pub fn new(
+        http: H,
+        year: u32,
+        base_url: impl Into<String>,
+        session: impl Into<String>,
+    ) -> Site<H> {
+        Site {
+            http,
+            year,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            session: session.into(),
+        }
+    }
+
+    fn day_url(&self, day: u32) -> String {
+        format!("{}/{}/day/{}", self.base_url, self.year, day)
+    }
+
+    pub fn input(&self, day: u32) -> io::Result<String> {
+        self.http
+            .get(&format!("{}/input", self.day_url(day)), &self.session)
+    }
+
+    pub fn submit(&self, day: u32, part: u32, answer: &str) -> io::Result<Verdict> {
+        let level = part.to_string();
+        let page = self.http.post_form(
+            &format!("{}/answer", self.day_url(day)),
+            &self.session,
+            &[("level", &level), ("answer", answer)],
+        )?;
+        Ok(parse_verdict(&page))
+    }
+}
+
+/// The session cookie, from `AOC_SESSION` or else the file
+/// `$XDG_CONFIG_HOME/aoc/session` (`~/.config/aoc/session` by default).
+pub fn session() -> io::Result<String> {
+    if let Ok(session) = std::env::var("AOC_SESSION") {
+        if !session.trim().is_empty() {
+            return Ok(clean_session(&session));
+        }
+    }
+
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    let Some(path) = config.map(|dir| dir.join("aoc/session")) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no session: set AOC_SESSION or HOME",
+        ));
+    };
+    match fs::read_to_string(&path) {
+        Ok(session) => Ok(clean_session(&session)),
+        Err(e) => Err(io::Error::new(
+            e.kind(),
+            format!(
+                "no session: set AOC_SESSION or write the cookie to {} ({})",
+                path.display(),
+                e
+            ),
+        )),
+    }
+}
+
+/// Accepts the cookie value with or without the `session=` it is copied with.
+fn clean_session(session: &str) -> String {
+    let session = session.trim();
+    session
+        .strip_prefix("session=")
+        .unwrap_or(session)
+        .to_string()
+}
+
+/// Where a day reads its input. Days 1 to 3 predate `input.txt`.
+pub fn input_path(day: u32) -> PathBuf {
+    input_path_in(Path::new(""), day)
+}
+
+/// [`input_path`] within the year's directory `dir`.
+fn input_path_in(dir: &Path, day: u32) -> PathBuf {
+    let legacy = dir.join(format!("day{}/part1.txt", day));
+    if day <= 3 && legacy.exists() {
+        legacy
+    } else {
+        dir.join(format!("day{}/input.txt", day))
+    }
+}
+
+/// Downloads the input for `day` into the year's directory `dir`, unless it
+/// is already there.
+pub fn fetch<H: Http>(site: &Site<H>, dir: &Path, day: u32, force: bool) -> io::Result<()> {
+    let path = input_path_in(dir, day);
+    if path.exists() && !force {
+        println!("{} is already downloaded", path.display());
+        return Ok(());
+    }
+
+    let input = site.input(day)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, &input)?;
+    println!("wrote {} ({} lines)", path.display(), input.lines().count());
+    Ok(())
+}
+
+/// Submits an answer, unless `dayN/input.answers` in the year's directory
+/// `dir` already has one for the part or the guess log rules it out, and
+/// records it there if it was right. Every answer the site checks goes into
+/// `dayN/guesses.txt`.
+pub fn submit<H: Http>(
+    site: &Site<H>,
+    dir: &Path,
+    day: u32,
+    part: u32,
+    answer: &str,
+) -> io::Result<Verdict> {
+    let answers_path = dir.join(answers::input_answers_path(day));
+    let log_path = dir.join(guesses::path(day));
+    let known = Answers::load(&answers_path)?;
+    if let Some(correct) = known.get(part) {
+        let verdict = if correct == answer {
+            Verdict::Correct
+        } else {
+            Verdict::Wrong
+        };
+        println!(
+            "Day {} Part {} is already solved with {}; not submitting",
+            day, part, correct
+        );
+        return Ok(verdict);
+    }
+
+    if let Err(rejection) = GuessLog::load(&log_path)?.check(part, answer) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not submitting {}: {}", answer, rejection),
+        ));
+    }
+
+    let verdict = site.submit(day, part, answer)?;
+    println!("Day {} Part {}: {} is {}", day, part, answer, verdict);
+    if let Some(outcome) = Outcome::from_verdict(&verdict) {
+        let guess = Guess {
+            part,
+            answer: answer.to_string(),
+            outcome,
+        };
+        GuessLog::append(&log_path, &guess)?;
+    }
+    if verdict == Verdict::Correct {
+        answers::record(&answers_path, part, answer)?;
+        println!("recorded in {}", answers_path.display());
+    }
+    Ok(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// Runs `f` on an empty scratch directory, standing in for the year's.
+    fn in_scratch_dir(name: &str, f: impl FnOnce(&Path)) {
+        let dir = std::env::temp_dir().join(format!("aoc-site-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        f(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Answers every request with `page` and remembers the URLs asked for.
+    struct FakeHttp {
+        page: String,
+        requests: RefCell<Vec<String>>,
+    }
+
+    impl Http for &FakeHttp {
+        fn get(&self, url: &str, _session: &str) -> io::Result<String> {
+            self.requests.borrow_mut().push(url.to_string());
+            Ok(self.page.clone())
+        }
+
+        fn post_form(
+            &self,
+            url: &str,
+            _session: &str,
+            _form: &[(&str, &str)],
+        ) -> io::Result<String> {
+            self.requests.borrow_mut().push(url.to_string());
+            Ok(self.page.clone())
+        }
+    }
+
+    fn fake(page: &str) -> FakeHttp {
+        FakeHttp {
+            page: page.to_string(),
+            requests: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn answer_page(text: &str) -> String {
+        format!(
+            "<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+            text
+        )
+    }
+
+    #[test]
+    fn verdicts() {
+        let cases = [
+            (
+                "That's the right answer! You are <em>one gold star</em> closer to finding the Chief Historian.",
+                Verdict::Correct,
+            ),
+            (
+                "That's not the right answer. If you're stuck, make sure you're using the full input data.",
+                Verdict::Wrong,
+            ),
+            (
+                "That's not the right answer; your answer is too high. If you're stuck, make sure you're using the full input data.",
+                Verdict::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low. If you're stuck, make sure you're using the full input data.",
+                Verdict::TooLow,
+            ),
+            (
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 34s left to wait.",
+                Verdict::RateLimited(Duration::from_secs(34)),
+            ),
+            (
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait.",
+                Verdict::RateLimited(Duration::from_secs(65)),
+            ),
+            (
+                "You don't seem to be solving the right level.  Did you already complete it?",
+                Verdict::WrongLevel,
+            ),
+        ];
+        for (text, verdict) in cases {
+            assert_eq!(parse_verdict(&answer_page(text)), verdict, "{}", text);
+        }
+    }
+
+    #[test]
+    fn fetch_keeps_existing_input_unless_forced() {
+        in_scratch_dir("fetch", |dir| {
+            let http = fake("new input\n");
+            let site = Site::new(&http, 2024, "https://example.test/", "cookie");
+            let input = dir.join("day5/input.txt");
+            fs::create_dir_all(dir.join("day5")).unwrap();
+            fs::write(&input, "old input\n").unwrap();
+
+            fetch(&site, dir, 5, false).unwrap();
+            assert_eq!(fs::read_to_string(&input).unwrap(), "old input\n");
+            assert!(http.requests.borrow().is_empty());
+
+            fetch(&site, dir, 5, true).unwrap();
+            assert_eq!(fs::read_to_string(&input).unwrap(), "new input\n");
+            assert_eq!(
+                *http.requests.borrow(),
+                ["https://example.test/2024/day/5/input"]
+            );
+
+            fetch(&site, dir, 6, false).unwrap();
+            let input = dir.join("day6/input.txt");
+            assert_eq!(fs::read_to_string(input).unwrap(), "new input\n");
+        });
+    }
+
+    #[test]
+    fn submit_records_only_correct_answers() {
+        in_scratch_dir("submit", |dir| {
+            let answers = dir.join(answers::input_answers_path(7));
+            fs::create_dir_all(dir.join("day7")).unwrap();
+
+            let wrong = fake(&answer_page(
+                "That's not the right answer; your answer is too low.",
+            ));
+            let site = Site::new(&wrong, 2024, "https://example.test", "cookie");
+            assert_eq!(submit(&site, dir, 7, 1, "41").unwrap(), Verdict::TooLow);
+            assert!(!answers.exists());
+            let log = GuessLog::load(&dir.join(guesses::path(7))).unwrap();
+            assert!(log.check(1, "40").is_err());
+
+            let right = fake(&answer_page("That's the right answer!"));
+            let site = Site::new(&right, 2024, "https://example.test", "cookie");
+            assert_eq!(submit(&site, dir, 7, 1, "42").unwrap(), Verdict::Correct);
+            assert_eq!(
+                *right.requests.borrow(),
+                ["https://example.test/2024/day/7/answer"]
+            );
+            assert_eq!(Answers::load(&answers).unwrap().get(1), Some("42"));
+
+            // Once known, the answer is checked locally and not sent again.
+            assert_eq!(submit(&site, dir, 7, 1, "43").unwrap(), Verdict::Wrong);
+            assert_eq!(right.requests.borrow().len(), 1);
+        });
+    }
+}
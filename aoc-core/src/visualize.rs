@@ -0,0 +1,194 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(clap::Args, Debug, Clone)]
+#[group(id = "animation")]
+pub struct Options {
+    /// Animate grid simulations (days 6, 14 and 15) in the terminal.
+    /// Keys: space pauses, `n` steps one frame while paused, `q` stops.
+    #[clap(long, action)]
+    pub visualize: bool,
+
+    /// Frames per second for --visualize
+    #[clap(long, default_value_t = 20)]
+    pub fps: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            visualize: false,
+            fps: 20,
+        }
+    }
+}
+
+/// Draws a character grid in place with ANSI escapes, highlighting the cells
+/// that changed since the previous frame.
+pub struct Renderer {
+    previous: Vec<Vec<char>>,
+    frame_time: Duration,
+    last_frame: Option<Instant>,
+    paused: bool,
+    stopped: bool,
+    keys: Option<Receiver<u8>>,
+    saved_tty: Option<String>,
+}
+
+const HIGHLIGHT: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+const CTRL_C: u8 = 0x03;
+
+impl Renderer {
+    pub fn new(fps: u32) -> Renderer {
+        // `-isig` makes Ctrl-C a key like any other instead of a signal that
+        // would end the process with the terminal still changed.
+        let saved_tty = stty(&["-g"]).filter(|_| stty(&["-icanon", "-echo", "-isig"]).is_some());
+
+        // Only listen for keys when the terminal was switched to unbuffered
+        // input, otherwise presses would only arrive after a newline. Keys
+        // are read from the terminal `stty` changed rather than stdin, which
+        // may be redirected.
+        let tty = saved_tty
+            .clone()
+            .and_then(|saved| Some((saved, File::open("/dev/tty").ok()?)));
+        let keys = tty.map(|(saved, tty)| {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                for byte in BufReader::new(tty).bytes() {
+                    match byte {
+                        // Ctrl-C still interrupts, just with the terminal
+                        // put back first.
+                        Ok(CTRL_C) => {
+                            restore_terminal(Some(&saved));
+                            std::process::exit(130);
+                        }
+                        Ok(b) if tx.send(b).is_ok() => {}
+                        _ => break,
+                    }
+                }
+            });
+            rx
+        });
+
+        print!("\x1b[2J\x1b[?25l");
+        Renderer {
+            previous: Vec::new(),
+            frame_time: Duration::from_secs(1) / fps.max(1),
+            last_frame: None,
+            paused: false,
+            stopped: false,
+            keys,
+            saved_tty,
+        }
+    }
+
+    /// Draws one frame with a status line underneath. Once the user quits the
+    /// animation this does nothing, so the simulation can finish at full speed.
+    pub fn frame(&mut self, grid: &[Vec<char>], status: &str) {
+        if self.stopped {
+            return;
+        }
+        if let Some(last) = self.last_frame {
+            let elapsed = last.elapsed();
+            if elapsed < self.frame_time {
+                thread::sleep(self.frame_time - elapsed);
+            }
+        }
+
+        let mut out = String::from("\x1b[H");
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &c) in row.iter().enumerate() {
+                let changed = !self.previous.is_empty()
+                    && self.previous.get(y).and_then(|r| r.get(x)) != Some(&c);
+                if changed {
+                    out.push_str(HIGHLIGHT);
+                    out.push(c);
+                    out.push_str(RESET);
+                } else {
+                    out.push(c);
+                }
+            }
+            out.push_str("\x1b[K\n");
+        }
+        out.push_str(status);
+        if self.paused {
+            out.push_str("  [paused: space resumes, n steps, q quits]");
+        }
+        out.push_str("\x1b[K\n");
+
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(out.as_bytes());
+        let _ = stdout.flush();
+
+        self.previous = grid.to_vec();
+        self.last_frame = Some(Instant::now());
+        self.handle_keys();
+    }
+
+    fn handle_keys(&mut self) {
+        let Some(keys) = &self.keys else {
+            return;
+        };
+
+        loop {
+            let key = if self.paused {
+                match keys.recv() {
+                    Ok(k) => k,
+                    Err(_) => return,
+                }
+            } else {
+                match keys.try_recv() {
+                    Ok(k) => k,
+                    Err(_) => return,
+                }
+            };
+
+            match key {
+                b'q' => {
+                    self.stopped = true;
+                    return;
+                }
+                b' ' => self.paused = !self.paused,
+                b'n' if self.paused => return,
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        restore_terminal(self.saved_tty.as_deref());
+    }
+}
+
+/// Shows the cursor again and puts back the terminal settings `stty -g`
+/// saved, if it changed them.
+fn restore_terminal(saved_tty: Option<&str>) {
+    print!("{}\x1b[?25h", RESET);
+    let _ = io::stdout().flush();
+    if let Some(saved) = saved_tty {
+        stty(&[saved.trim()]);
+    }
+}
+
+/// Runs `stty` against the controlling terminal, returning its output on
+/// success. This keeps raw key handling free of platform-specific crates.
+fn stty(args: &[&str]) -> Option<String> {
+    let tty = File::open("/dev/tty").ok()?;
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::from(tty))
+        .output()
+        .ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
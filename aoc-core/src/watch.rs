@@ -0,0 +1,171 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::answers::{self, Answers};
+
+const POLL: Duration = Duration::from_millis(500);
+
+/// The shared sources, as built, for `aoc watch` to rebuild on changes to.
+pub const CORE_SOURCES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+
+/// What `aoc watch` reruns and where it looks for changes.
+pub struct Watch {
+    /// The year's directory, holding the day's data files.
+    pub year_dir: PathBuf,
+    /// The source directories of the crates the command builds, wherever
+    /// they were built from; see [`CORE_SOURCES`].
+    pub sources: Vec<PathBuf>,
+    pub day: u32,
+    pub sample: Option<u32>,
+}
+
+impl Watch {
+    /// The sources and the day's data files.
+    fn watched(&self) -> Vec<PathBuf> {
+        let mut watched = self.sources.clone();
+        watched.push(self.year_dir.join(format!("day{}", self.day)));
+        watched
+    }
+
+    fn expected(&self) -> io::Result<Answers> {
+        let path = match self.sample {
+            Some(k) => answers::sample_answers_path(self.day, k),
+            None => answers::input_answers_path(self.day),
+        };
+        Answers::load(&self.year_dir.join(path))
+    }
+
+    /// Reruns `command`, which should rebuild and run the day, whenever a
+    /// watched file changes, until the process is killed. Polling mtimes
+    /// keeps this free of platform-specific file watching.
+    pub fn run(&self, mut command: Command) -> io::Result<()> {
+        let mut previous: Vec<(u32, String)> = Vec::new();
+        let mut seen = None;
+
+        loop {
+            let before = snapshot(&self.watched());
+            if seen.as_ref() == Some(&before) {
+                thread::sleep(POLL);
+                continue;
+            }
+
+            print!("\x1b[2J\x1b[H");
+            println!("Watching day {}{}", self.day, self.sample_label());
+            io::stdout().flush()?;
+
+            let start = Instant::now();
+            let output = command.output()?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            print!("{}", stdout);
+            if !output.status.success() {
+                print!("{}", String::from_utf8_lossy(&output.stderr));
+            }
+
+            let current = reported_answers(&stdout, self.day);
+            println!();
+            self.print_summary(&current, &previous)?;
+            println!("({:?}, waiting for changes)", start.elapsed());
+
+            if !current.is_empty() {
+                previous = current;
+            }
+            // Files the day writes itself shouldn't trigger another run, but
+            // source edits made while it was building should.
+            let after = snapshot(&self.watched());
+            seen = (sources(&before) == sources(&after)).then_some(after);
+        }
+    }
+
+    fn sample_label(&self) -> String {
+        match self.sample {
+            Some(k) => format!(" on sample {}", k),
+            None => String::new(),
+        }
+    }
+
+    /// One line per part, green when it matches the expected answer and red
+    /// when it doesn't, noting how it changed since the last run.
+    fn print_summary(
+        &self,
+        current: &[(u32, String)],
+        previous: &[(u32, String)],
+    ) -> io::Result<()> {
+        if current.is_empty() {
+            println!("no answers");
+            return Ok(());
+        }
+
+        let expected = self.expected()?;
+        for (part, value) in current {
+            let (colour, status) = match expected.get(*part) {
+                Some(want) if want == value => ("\x1b[32m", "ok".to_string()),
+                Some(want) => ("\x1b[31m", format!("expected {}", want)),
+                None => ("", "no expected answer".to_string()),
+            };
+            let change = match previous.iter().find(|(p, _)| p == part) {
+                Some((_, before)) if before != value => format!(", was {}", before),
+                _ => String::new(),
+            };
+            println!(
+                "{}Part {}: {} ({}{})\x1b[0m",
+                colour, part, value, status, change
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Answers in the `Day N Part P: value` lines printed by `runner::answer`,
+/// keeping the last one for each part.
+fn reported_answers(output: &str, day: u32) -> Vec<(u32, String)> {
+    let prefix = format!("Day {} Part ", day);
+    let mut answers: Vec<(u32, String)> = Vec::new();
+    for line in output.lines() {
+        let Some((part, value)) = line
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split_once(": "))
+        else {
+            continue;
+        };
+        let Ok(part) = part.parse() else {
+            continue;
+        };
+        answers.retain(|(p, _)| *p != part);
+        answers.push((part, value.to_string()));
+    }
+    answers.sort();
+    answers
+}
+
+type Snapshot = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// Every file under `roots` with its modification time, sorted by path.
+fn snapshot(roots: &[PathBuf]) -> Snapshot {
+    let mut files = Vec::new();
+    let mut pending: Vec<PathBuf> = roots.to_vec();
+    while let Some(path) = pending.pop() {
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            if let Ok(entries) = fs::read_dir(&path) {
+                pending.extend(entries.filter_map(|e| e.ok()).map(|e| e.path()));
+            }
+        } else {
+            files.push((path, meta.modified().ok()));
+        }
+    }
+    files.sort();
+    files
+}
+
+fn sources(snapshot: &Snapshot) -> Vec<&(PathBuf, Option<SystemTime>)> {
+    snapshot
+        .iter()
+        .filter(|(path, _)| path.extension().is_some_and(|e| e == "rs"))
+        .collect()
+}
@@ -0,0 +1,406 @@
+use aoc_core::runner::{self, RunOptions, Year};
+use aoc_core::{answers, bench, budget, input, scaffold, site, watch};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: aoc_core::alloc_stats::Counting = aoc_core::alloc_stats::Counting;
+
+/// The newest year, run when `--year` isn't given.
+const LATEST_YEAR: u32 = 2024;
+
+/// This is a simple program
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// Which year's puzzles to work on. Paths are relative to that year's
+    /// directory, e.g. 2024/
+    #[clap(long, global = true, default_value_t = LATEST_YEAR)]
+    year: u32,
+
+    /// The year's directory, holding dayN/. Defaults to the working
+    /// directory if it is that directory or holds it, else to where the
+    /// year was built
+    #[clap(long, global = true)]
+    dir: Option<PathBuf>,
+
+    #[clap(long, short, action)]
+    all: bool,
+
+    #[clap(long, short, action)]
+    day: Option<u32>,
+
+    /// Run the day on dayN/sample.txt (1) or dayN/sampleK.txt instead of its
+    /// input
+    #[clap(long, conflicts_with = "all")]
+    sample: Option<u32>,
+
+    /// Check the answers against dayN/input.answers, or against the bounds
+    /// in dayN/guesses.txt for parts that aren't confirmed yet
+    #[clap(long, action)]
+    verify: bool,
+
+    /// Print a tree of the time spent in each `span!` after every day. Needs
+    /// `--features profile`
+    #[clap(long, action)]
+    profile: bool,
+
+    /// Benchmark the days in budgets.toml, or just `--day`, and fail if one
+    /// is over its budget by more than the tolerance
+    #[clap(long, action, conflicts_with_all = ["all", "sample", "verify"])]
+    check_budget: bool,
+
+    /// Timed runs per day when benchmarking
+    #[clap(long, default_value_t = bench::DEFAULT_RUNS, requires = "check_budget")]
+    runs: usize,
+
+    /// How far over budget a day may go, in percent
+    #[clap(long, default_value_t = 10.0, requires = "check_budget")]
+    budget_tolerance: f64,
+
+    /// Save this check's timings to budgets.baseline.json for the next one
+    /// to compare against
+    #[clap(long, action, requires = "check_budget")]
+    save_baseline: bool,
+
+    #[clap(flatten)]
+    y2024: aoc_2024::Options,
+}
+
+impl Args {
+    fn run_options(&self) -> RunOptions {
+        RunOptions {
+            verify: self.verify,
+            sample: self.sample,
+            profile: self.profile,
+        }
+    }
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Create a new day from the template and register it with the runner
+    New {
+        #[clap(value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+    },
+
+    /// Download a day's input, unless it is already on disk. The session
+    /// cookie comes from AOC_SESSION or ~/.config/aoc/session
+    Fetch {
+        #[clap(value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+
+        /// Download it again even if it is already on disk
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Submit an answer; correct answers are saved to dayN/input.answers
+    Submit {
+        #[clap(value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+
+        #[clap(value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: u32,
+
+        /// The answer to submit; if left out, the day is run for it
+        answer: Option<String>,
+    },
+
+    /// Rebuild and rerun a day whenever its code or data changes, comparing
+    /// the answers with the expected ones
+    Watch {
+        #[clap(long, short)]
+        day: u32,
+
+        /// Run on this sample rather than the input
+        #[clap(long)]
+        sample: Option<u32>,
+
+        /// Extra options passed on to the day, after `--`
+        #[clap(last = true)]
+        options: Vec<String>,
+    },
+
+    /// Check that a day's input has the shape the day expects, listing the
+    /// lines that don't and what normalising the input would change
+    CheckInput {
+        #[clap(value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+
+        /// Check this sample instead of the input
+        #[clap(long)]
+        sample: Option<u32>,
+
+        /// Check this file, relative to the year's directory, instead of the
+        /// input
+        #[clap(long, conflicts_with = "sample")]
+        file: Option<std::path::PathBuf>,
+    },
+
+    /// Benchmark every day, or one, and keep or compare the results in bench/
+    Bench {
+        /// Only this day
+        #[clap(long, short)]
+        day: Option<u32>,
+
+        /// Timed runs per day
+        #[clap(long, default_value_t = bench::DEFAULT_RUNS)]
+        runs: usize,
+
+        /// Save the results as bench/<NAME>.json
+        #[clap(long, value_name = "NAME")]
+        save: Option<String>,
+
+        /// Compare two saved snapshots instead of benchmarking
+        #[clap(long, num_args = 2, value_names = ["A", "B"], conflicts_with_all = ["save", "runs"])]
+        compare: Vec<String>,
+
+        /// Show each day's median across all saved snapshots instead of
+        /// benchmarking
+        #[clap(long, conflicts_with_all = ["save", "runs", "compare"])]
+        history: bool,
+    },
+}
+
+fn main() {
+    let args = Args::parse();
+    let ok = match args.year {
+        2024 => match aoc_2024::year(args.dir.as_deref()) {
+            Ok(year) => run_year(&year, &args.y2024, &args),
+            Err(e) => {
+                println!("Could not find the {} directory: {}", args.year, e);
+                false
+            }
+        },
+        year => {
+            println!("There are no solutions for {} yet.", year);
+            false
+        }
+    };
+
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+/// Carries out the command line for one year, whose days take `options`.
+/// Returns false if anything failed: a day or command that hit an error, an
+/// answer `--verify` found wrong, a day over budget, an input that doesn't
+/// fit, or a submitted answer the site didn't accept.
+fn run_year<O>(year: &Year<O>, options: &O, args: &Args) -> bool {
+    if let Err(e) = year.enter() {
+        println!("Could not enter {}: {}", year.dir.display(), e);
+        return false;
+    }
+
+    if let Some(command) = &args.command {
+        match command {
+            Command::New { day } => match scaffold::new_day(&year.dir, *day) {
+                Ok(_) => true,
+                Err(e) => {
+                    println!("Could not create day {}: {}", day, e);
+                    false
+                }
+            },
+            Command::Fetch { day, force } => {
+                match site::Site::from_env(year.year)
+                    .and_then(|s| site::fetch(&s, &year.dir, *day, *force))
+                {
+                    Ok(_) => true,
+                    Err(e) => {
+                        println!("Could not fetch day {}: {}", day, e);
+                        false
+                    }
+                }
+            }
+            Command::Submit { day, part, answer } => {
+                match submit(year, options, *day, *part, answer.as_deref()) {
+                    Ok(verdict) => verdict == site::Verdict::Correct,
+                    Err(e) => {
+                        println!("Could not submit day {} part {}: {}", day, part, e);
+                        false
+                    }
+                }
+            }
+            Command::Watch {
+                day,
+                sample,
+                options,
+            } => {
+                let watch = watch::Watch {
+                    year_dir: year.dir.clone(),
+                    sources: vec![
+                        PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src")),
+                        PathBuf::from(watch::CORE_SOURCES),
+                        year.crate_dir.join("src"),
+                    ],
+                    day: *day,
+                    sample: *sample,
+                };
+                match watch.run(rerun(year, *day, *sample, options)) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        println!("Could not watch day {}: {}", day, e);
+                        false
+                    }
+                }
+            }
+            Command::CheckInput { day, sample, file } => {
+                check_input(year, *day, *sample, file.as_deref())
+            }
+            Command::Bench {
+                day,
+                runs,
+                save,
+                compare,
+                history,
+            } => {
+                match run_bench(
+                    year,
+                    options,
+                    *day,
+                    *runs,
+                    save.as_deref(),
+                    compare,
+                    *history,
+                ) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        println!("Could not benchmark: {}", e);
+                        false
+                    }
+                }
+            }
+        }
+    } else if args.check_budget {
+        let check = budget::Check {
+            runs: args.runs,
+            tolerance: args.budget_tolerance,
+            day: args.day,
+            save_baseline: args.save_baseline,
+        };
+        budget::check(year, options, &check).unwrap_or_else(|e| {
+            println!("Could not check budgets: {}", e);
+            false
+        })
+    } else if let Some(day) = args.day {
+        year.run_day(day, options, &args.run_options())
+    } else if args.all {
+        year.run_all(options, &args.run_options())
+    } else {
+        println!("Please specify a day using the --day option.");
+        false
+    }
+}
+
+/// `cargo run` for this binary with one day, for `aoc watch`.
+fn rerun<O>(
+    year: &Year<O>,
+    day: u32,
+    sample: Option<u32>,
+    options: &[String],
+) -> std::process::Command {
+    let cargo = std::env::var_os("CARGO").unwrap_or("cargo".into());
+    let mut command = std::process::Command::new(cargo);
+    command.args(["run", "--quiet", "--package", env!("CARGO_PKG_NAME")]);
+    // The working directory is the year's, which needn't be in the workspace.
+    command.args([
+        "--manifest-path",
+        concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"),
+    ]);
+    command.args(["--bin", env!("CARGO_BIN_NAME"), "--"]);
+    command.args(["--year", &year.year.to_string()]);
+    command.arg("--dir").arg(&year.dir);
+    command.args(["--day", &day.to_string()]);
+    if let Some(k) = sample {
+        command.args(["--sample", &k.to_string()]);
+    }
+    command.args(options);
+    command
+}
+
+/// `aoc check-input`, on the day's input unless given a sample or a file.
+fn check_input<O>(year: &Year<O>, day: u32, sample: Option<u32>, file: Option<&Path>) -> bool {
+    let path = match (file, sample) {
+        (Some(file), _) => file.to_path_buf(),
+        (None, Some(k)) => answers::sample_path(day, k),
+        (None, None) => site::input_path(day),
+    };
+    let shape = year.find(day).map_or(input::Shape::Text, |s| s.input);
+    input::check_file(&path, &shape).unwrap_or_else(|e| {
+        println!("Could not check {}: {}", path.display(), e);
+        false
+    })
+}
+
+/// `aoc bench`: compares two snapshots, shows the history, or measures the
+/// days and maybe saves them.
+fn run_bench<O>(
+    year: &Year<O>,
+    options: &O,
+    day: Option<u32>,
+    runs: usize,
+    save: Option<&str>,
+    compare: &[String],
+    history: bool,
+) -> std::io::Result<()> {
+    if let [a, b] = compare {
+        bench::print_comparison(&bench::load_named(a)?, &bench::load_named(b)?);
+        return Ok(());
+    }
+    if history {
+        return bench::print_history(day);
+    }
+
+    // Check the name before spending time on the runs.
+    let path = save.map(bench::snapshot_path).transpose()?;
+    let days: Vec<u32> = match day {
+        Some(day) => vec![day],
+        None => year.solutions.iter().map(|s| s.day).collect(),
+    };
+    let mut snapshot = bench::measure_days(year, options, &days, runs)?;
+    bench::print_table(&snapshot);
+    if let Some(path) = path {
+        snapshot.save(&path)?;
+        println!("Saved to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Submits `answer`, or the answer the day itself reports for `part`.
+fn submit<O>(
+    year: &Year<O>,
+    options: &O,
+    day: u32,
+    part: u32,
+    answer: Option<&str>,
+) -> Result<site::Verdict, Box<dyn std::error::Error>> {
+    let answer = match answer {
+        Some(answer) => answer.to_string(),
+        None => {
+            let solution = year
+                .find(day)
+                .ok_or(format!("day {} is not implemented yet", day))?;
+            year.enter_day(day, None)?;
+            runner::run_for_answers(solution, options)?
+                .into_iter()
+                .rev()
+                .find(|(p, _)| *p == part)
+                .map(|(_, value)| value)
+                .ok_or(format!("day {} did not report part {}", day, part))?
+        }
+    };
+    Ok(site::submit(
+        &site::Site::from_env(year.year)?,
+        &year.dir,
+        day,
+        part,
+        &answer,
+    )?)
+}